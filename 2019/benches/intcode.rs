@@ -0,0 +1,89 @@
+use aoc::intcode::{Computer, State};
+use itertools::Itertools;
+use std::fs;
+use std::time::{Duration, Instant};
+
+fn load(day: usize) -> Vec<isize> {
+    fs::read_to_string(format!("data/day{}.txt", day))
+        .expect("Missing input")
+        .trim()
+        .split(',')
+        .map(|s| s.parse::<isize>().unwrap())
+        .collect()
+}
+
+fn noun_verb_search(base: &Computer) -> isize {
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut c = base.clone();
+            c.set_noun_verb(noun, verb);
+            c.run().unwrap();
+            if c.get_result() == 19690720 {
+                return 100 * noun + verb;
+            }
+        }
+    }
+    panic!("No result")
+}
+
+fn boost(base: &Computer) -> isize {
+    let mut c = base.clone();
+    c.push_input(2);
+    c.run().unwrap();
+    c.pop_output().unwrap()
+}
+
+const RUNS: usize = 10;
+
+// Round robin in one thread, so that only the interpreter is measured and not the thread
+// startup of the `Circuit` used by the day 7 solution.
+fn feedback_search(base: &Computer) -> isize {
+    (5..10).permutations(5).map(|phases| {
+        let mut amps = phases.iter().map(|p| {
+            let mut c = base.clone();
+            c.push_input(*p);
+            c
+        }).collect::<Vec<_>>();
+        let mut signal = 0;
+        loop {
+            let mut state = State::NeedsInput;
+            for c in amps.iter_mut() {
+                c.push_input(signal);
+                state = c.resume();
+                signal = c.pop_output().unwrap();
+            }
+            if state == State::Halted {
+                return signal;
+            }
+        }
+    }).max().unwrap()
+}
+
+fn time<F: FnMut() -> isize>(mut f: F) -> (isize, Duration) {
+    let mut best = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = f();
+        best = best.min(start.elapsed());
+    }
+    (result, best)
+}
+
+fn bench(name: &str, code: &[isize], f: fn(&Computer) -> isize) {
+    let plain = Computer::load(code);
+    let mut compiled = Computer::load(code);
+    compiled.compile();
+
+    let (r1, t1) = time(|| f(&plain));
+    let (r2, t2) = time(|| f(&compiled));
+    assert_eq!(r1, r2);
+    println!("{:<10} interpreted {:>10.2?}  compiled {:>10.2?}  speedup {:.1}x",
+        name, t1, t2, t1.as_secs_f64() / t2.as_secs_f64());
+}
+
+fn main() {
+    bench("day2", &load(2), noun_verb_search);
+    bench("day7", &load(7), feedback_search);
+    bench("day9", &load(9), boost);
+}
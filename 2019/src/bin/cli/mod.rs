@@ -0,0 +1,22 @@
+use anyhow::Result;
+use aoc::intcode::Computer;
+
+pub fn load(arg: &str) -> Result<Computer> {
+    match arg.trim_start_matches("day").parse::<usize>() {
+        Ok(day) => Computer::load_file(format!("data/day{}.txt", day)),
+        Err(_) => Computer::load_file(arg),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_day() {
+        let day9 = load("9").unwrap();
+        assert_eq!(load("day9").unwrap().snapshot(), day9.snapshot());
+        assert_eq!(load("data/day9.txt").unwrap().snapshot(), day9.snapshot());
+        assert!(load("day99").is_err());
+    }
+}
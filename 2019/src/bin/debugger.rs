@@ -0,0 +1,181 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use aoc::intcode::{Breakpoint, Budget, Computer, Debugger, Line, Opcode, Stop};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+mod cli;
+
+static HELP: &str = "\
+s, step [n]          execute n instructions (default 1)
+c, cont [n]          continue until breakpoint, watch or halt, at most n steps (default 10000000)
+b, break <addr>      break on address
+bo <mnemonic>        break on opcode
+bw <addr>            break on memory write
+bl                   list breakpoints and watches
+bd <kind> <value>    delete breakpoint (kind: b, bo, bw)
+w, watch <addr>      break when memory value changes
+wd <addr>            delete watch
+r, regs              show ip, base and I/O queues
+m, mem <from> [to]   show memory range (at most 4096 cells)
+d, dis [addr] [n]    disassemble n instructions (default 10 from ip)
+map                  show executed code (c), data (d) and mixed (m) addresses
+dot <file>           write control-flow graph in Graphviz DOT format
+i, in <values...>    push values to input queue
+o, out               pop all values from output queue
+save <file>          save machine state snapshot
+q, quit              exit";
+
+const CONT_STEPS: u64 = 10_000_000;
+const MEM_RANGE: usize = 4096;
+
+fn arg<T: FromStr>(args: &[&str], idx: usize) -> Result<Option<T>> {
+    args.get(idx).map(|s| s.parse::<T>().map_err(|_| anyhow!("Invalid argument `{}`", s))).transpose()
+}
+
+fn required<T: FromStr>(args: &[&str], idx: usize) -> Result<T> {
+    arg(args, idx)?.ok_or_else(|| anyhow!("Missing argument"))
+}
+
+fn breakpoint(kind: &str, args: &[&str], idx: usize) -> Result<Breakpoint> {
+    Ok(match kind {
+        "b" | "break" => Breakpoint::Address(required(args, idx)?),
+        "bo" => Breakpoint::Opcode(required::<String>(args, idx)?.parse::<Opcode>()?),
+        "bw" => Breakpoint::Write(required(args, idx)?),
+        _ => bail!("Invalid breakpoint kind `{}`", kind),
+    })
+}
+
+fn disassemble(computer: &Computer, from: usize, count: usize) {
+    let mut addr = from;
+    for _ in 0..count {
+        if addr >= computer.memory_len() {
+            break;
+        }
+        match computer.decode_at(addr) {
+            Ok(item) => {
+                let size = item.size();
+                println!("{} {}", if addr == computer.ip() { "=>" } else { "  " }, Line { addr, item });
+                addr += size;
+            },
+            Err(_) => {
+                println!("   {:>6}: {:<5} {}", addr, ".data", computer.peek(addr));
+                addr += 1;
+            },
+        }
+    }
+}
+
+fn execute(debugger: &mut Debugger, command: &str, args: &[&str]) -> Result<bool> {
+    match command {
+        "s" | "step" => {
+            let mut stop = Stop::Step;
+            for _ in 0..arg(args, 0)?.unwrap_or(1) {
+                stop = debugger.step()?;
+                if stop != Stop::Step {
+                    break;
+                }
+            }
+            println!("{}", stop);
+            disassemble(debugger.computer(), debugger.computer().ip(), 1);
+        },
+        "c" | "cont" => {
+            debugger.computer_mut().set_budget(Budget::steps(arg(args, 0)?.unwrap_or(CONT_STEPS)));
+            let stop = debugger.cont();
+            debugger.computer_mut().clear_budget();
+            println!("{}", stop?);
+            disassemble(debugger.computer(), debugger.computer().ip(), 1);
+        },
+        "b" | "break" | "bo" | "bw" => {
+            let bp = breakpoint(command, args, 0)?;
+            if !debugger.add_breakpoint(bp) {
+                println!("Breakpoint on {} already exists", bp);
+            }
+        },
+        "bd" => {
+            let bp = breakpoint(&required::<String>(args, 0)?, args, 1)?;
+            if !debugger.remove_breakpoint(bp) {
+                println!("No breakpoint on {}", bp);
+            }
+        },
+        "bl" => {
+            for bp in debugger.breakpoints() {
+                println!("breakpoint on {}", bp);
+            }
+            for (addr, value) in debugger.watches() {
+                println!("watch on {} = {}", addr, value);
+            }
+        },
+        "w" | "watch" => debugger.watch(required(args, 0)?),
+        "wd" => {
+            if !debugger.unwatch(required(args, 0)?) {
+                println!("No such watch");
+            }
+        },
+        "r" | "regs" => {
+            let c = debugger.computer();
+            println!("ip: {}  base: {}", c.ip(), c.base());
+            println!("input: {:?}", c.input());
+            println!("output: {:?}", c.output());
+        },
+        "m" | "mem" => {
+            let from = required::<usize>(args, 0)?;
+            let to = arg::<usize>(args, 1)?.unwrap_or(from);
+            ensure!(to.saturating_sub(from) < MEM_RANGE, "Range too large, at most {} cells", MEM_RANGE);
+            for row in (from..=to).collect::<Vec<_>>().chunks(8) {
+                println!("{:>6}:{}", row[0], row.iter().map(|a| format!(" {:>8}", debugger.computer().peek(*a))).collect::<String>());
+            }
+        },
+        "d" | "dis" => {
+            let from = arg(args, 0)?.unwrap_or_else(|| debugger.computer().ip());
+            disassemble(debugger.computer(), from, arg(args, 1)?.unwrap_or(10));
+        },
+        "dot" => std::fs::write(required::<String>(args, 0)?, debugger.computer().control_flow().to_dot())?,
+        "map" => print!("{}", debugger.computer().code_map().map(|m| m.to_string()).unwrap_or_default()),
+        "i" | "in" => {
+            for value in args.iter().map(|s| s.parse::<isize>()).collect::<Result<Vec<_>, _>>()? {
+                debugger.computer_mut().push_input(value);
+            }
+        },
+        "o" | "out" => {
+            let mut values = Vec::<isize>::new();
+            while let Ok(value) = debugger.computer_mut().pop_output() {
+                values.push(value);
+            }
+            values.reverse();
+            println!("{:?}", values);
+        },
+        "save" => debugger.computer().save_snapshot(required::<String>(args, 0)?)?,
+        "h" | "help" => println!("{}", HELP),
+        "q" | "quit" => return Ok(false),
+        _ => bail!("Unknown command `{}`, type `h` for help", command),
+    }
+    Ok(true)
+}
+
+fn main() -> Result<()> {
+    let arg = std::env::args().nth(1).ok_or_else(|| anyhow!("Usage: debugger <day number, program or snapshot file>"))?;
+    let mut computer = cli::load(&arg)?;
+    computer.track_code();
+    let mut debugger = Debugger::new(computer);
+    disassemble(debugger.computer(), 0, 1);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.is_empty() {
+            continue;
+        }
+        match execute(&mut debugger, words[0], &words[1..]) {
+            Ok(true) => {},
+            Ok(false) => return Ok(()),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
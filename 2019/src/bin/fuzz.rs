@@ -0,0 +1,26 @@
+use anyhow::{bail, Result};
+use aoc::intcode;
+
+fn arg<T: std::str::FromStr>(idx: usize, default: T) -> Result<T> {
+    match std::env::args().nth(idx) {
+        Some(s) => match s.parse::<T>() {
+            Ok(value) => Ok(value),
+            Err(_) => bail!("Usage: fuzz [iterations] [seed] [step limit]"),
+        },
+        None => Ok(default),
+    }
+}
+
+fn main() -> Result<()> {
+    let iterations = arg(1, 100_000)?;
+    let seed = arg(2, 2019)?;
+    let limit = arg(3, 10_000)?;
+
+    match intcode::fuzz(seed, iterations, limit) {
+        Some(mismatch) => bail!("{}", mismatch),
+        None => {
+            println!("{} programs agree with the reference interpreter", iterations);
+            Ok(())
+        },
+    }
+}
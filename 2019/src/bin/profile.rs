@@ -0,0 +1,34 @@
+use anyhow::{anyhow, Result};
+use aoc::intcode::State;
+
+mod cli;
+
+fn main() -> Result<()> {
+    let usage = || anyhow!("Usage: profile <day number, program or snapshot file> [--folded <file>] [inputs...]");
+    let mut args = std::env::args().skip(1);
+    let mut computer = cli::load(&args.next().ok_or_else(usage)?)?;
+    let mut folded = None;
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), arg.parse::<isize>()) {
+            ("--folded", _) => folded = Some(args.next().ok_or_else(usage)?),
+            (_, Ok(value)) => computer.push_input(value),
+            (line, Err(_)) => computer.push_line(line),
+        }
+    }
+
+    computer.start_profile();
+    let state = computer.resume();
+    let profile = computer.take_profile().unwrap();
+    print!("{}", profile);
+    if let Some(path) = folded {
+        profile.save_folded(path)?;
+    }
+    match state {
+        State::Halted => Ok(()),
+        State::NeedsInput => Err(anyhow!("End of input while program waits for more")),
+        State::Fault(fault) => Err(fault.into()),
+        State::BudgetExhausted => Err(anyhow!("Budget exhausted")),
+        State::Stopped => Err(anyhow!("Stopped")),
+        State::Output(_) => unreachable!("Resuming never stops on output"),
+    }
+}
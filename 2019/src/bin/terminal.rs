@@ -0,0 +1,22 @@
+use anyhow::{anyhow, Result};
+use aoc::intcode::State;
+
+mod cli;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let arg = args.next().ok_or_else(|| anyhow!("Usage: terminal <day number, program or snapshot file> [input lines...]"))?;
+    let mut computer = cli::load(&arg)?;
+    for line in args {
+        computer.push_line(&line);
+    }
+
+    match computer.interact()? {
+        State::Halted => Ok(()),
+        State::NeedsInput => Err(anyhow!("End of input while program waits for more")),
+        State::Fault(fault) => Err(fault.into()),
+        State::BudgetExhausted => Err(anyhow!("Budget exhausted")),
+        State::Stopped => Err(anyhow!("Stopped")),
+        State::Output(_) => unreachable!("Terminal output never interrupts"),
+    }
+}
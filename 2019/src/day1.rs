@@ -18,9 +18,16 @@ fn sum_fuel(masses: &[usize], fuel_fn: fn (usize) -> usize) -> usize {
     masses.into_iter().fold(0, |acc, i| acc + fuel_fn(*i))
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let masses  = data.lines().map(|s| s.parse::<usize>()).collect::<Result<Vec<_>,_>>()?;
-    Ok((sum_fuel(&masses, fuel_for_mass), sum_fuel(&masses, fuel_for_mass_and_fuel)))
+pub fn parse(data: &str) -> Result<Vec<usize>> {
+    Ok(data.lines().map(|s| s.parse::<usize>()).collect::<Result<Vec<_>,_>>()?)
+}
+
+pub fn part1(masses: &[usize]) -> Result<usize> {
+    Ok(sum_fuel(masses, fuel_for_mass))
+}
+
+pub fn part2(masses: &[usize]) -> Result<usize> {
+    Ok(sum_fuel(masses, fuel_for_mass_and_fuel))
 }
 
 #[cfg(test)]
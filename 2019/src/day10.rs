@@ -78,11 +78,19 @@ fn vaporize_order(coords: &mut Vec<Coord>, from: &Coord) -> Vec<Coord> {
     order
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let mut asteroids = parse_map(data);
-    let (station_coords, visible_asteroids) = most_direct_sight(&asteroids);
-    let vaporized_coords = vaporize_order(&mut asteroids, &station_coords);
-    Ok((visible_asteroids, coord_to_num(&vaporized_coords[199])?))
+pub fn parse(data: &str) -> Result<Vec<Coord>> {
+    Ok(parse_map(data))
+}
+
+pub fn part1(asteroids: &[Coord]) -> Result<usize> {
+    let (_, visible_asteroids) = most_direct_sight(asteroids);
+    Ok(visible_asteroids)
+}
+
+pub fn part2(asteroids: &[Coord]) -> Result<usize> {
+    let (station_coords, _) = most_direct_sight(asteroids);
+    let vaporized_coords = vaporize_order(&mut asteroids.to_vec(), &station_coords);
+    coord_to_num(&vaporized_coords[199])
 }
 
 #[cfg(test)]
@@ -1,50 +1,59 @@
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
-use crate::intcode::Computer;
+use crate::intcode::{Computer, Device, Program, State};
 use crate::util::{Coord, Dir};
 
 struct Robot {
-    computer: Computer,
+    hull: HashMap<Coord, bool>,
     pos: Coord,
     dir: Dir,
+    painted: bool,
 }
 
 impl Robot {
-    fn new(code: &[isize]) -> Robot {
+    fn new(hull: HashMap<Coord, bool>) -> Robot {
         Robot {
-            computer: Computer::load(code),
+            hull,
             pos: (0, 0),
             dir: Dir::Up,
+            painted: false,
         }
     }
 
-    fn run(&mut self, hull: &mut HashMap<Coord, bool>) -> Result<()> {
-        loop {
-            let ocolor = *hull.get(&self.pos).unwrap_or(&false);
-            self.computer.push_input(if ocolor { 1 } else { 0 });
+    fn run(&mut self, code: &[isize]) -> Result<()> {
+        match Computer::load(code).run_with(self)? {
+            State::Halted if !self.painted => Ok(()),
+            State::Halted => Err(anyhow!("Missing output")),
+            State::Fault(fault) => Err(fault.into()),
+            _ => Err(anyhow!("Missing input")),
+        }
+    }
+}
+
+impl Device for Robot {
+    fn input(&mut self) -> Option<isize> {
+        Some(if *self.hull.get(&self.pos).unwrap_or(&false) { 1 } else { 0 })
+    }
 
-            if !self.computer.run_until_output()? {
-                return Ok(())
-            }
-            let ncolor = match self.computer.pop_output()? {
+    fn output(&mut self, value: isize) -> Result<()> {
+        if !self.painted {
+            let color = match value {
                 0 => false,
                 1 => true,
                 _ => return Err(anyhow!("Invalid color")),
             };
-            hull.insert(self.pos, ncolor);
-
-            if !self.computer.run_until_output()? {
-                return Err(anyhow!("Missing output"));
-            }
-            let turn = match self.computer.pop_output()? {
+            self.hull.insert(self.pos, color);
+        } else {
+            let turn = match value {
                 0 => false,
                 1 => true,
                 _ => return Err(anyhow!("Invalid direction")),
             };
             self.dir = self.dir.turn(turn);
-
             self.pos = self.dir.apply(&self.pos);
         }
+        self.painted = !self.painted;
+        Ok(())
     }
 }
 
@@ -63,17 +72,20 @@ fn draw(hull: &HashMap<Coord, bool>) -> String {
     ).fold(String::new(), |s, l| s + "\n" + &l)
 }
 
-pub fn solution(data: &str) -> Result<(usize, String)> {
-    let code = data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?;
-
-    let mut robot1 = Robot::new(&code);
-    let mut hull1 = HashMap::<Coord, bool>::new();
-    robot1.run(&mut hull1)?;
+pub fn parse(data: &str) -> Result<Program> {
+    Ok(data.parse::<Program>()?)
+}
 
-    let mut robot2 = Robot::new(&code);
-    let mut hull2 = HashMap::<Coord, bool>::new();
-    hull2.insert((0, 0), true);
-    robot2.run(&mut hull2)?;
+pub fn part1(program: &Program) -> Result<usize> {
+    let mut robot = Robot::new(HashMap::<Coord, bool>::new());
+    robot.run(program.code())?;
+    Ok(robot.hull.len())
+}
 
-    Ok((hull1.len(), draw(&hull2)))
+pub fn part2(program: &Program) -> Result<String> {
+    let mut hull = HashMap::<Coord, bool>::new();
+    hull.insert((0, 0), true);
+    let mut robot = Robot::new(hull);
+    robot.run(program.code())?;
+    Ok(draw(&robot.hull))
 }
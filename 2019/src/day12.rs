@@ -97,18 +97,20 @@ fn find_repeat(positions: &[Coord3], velocities: &[Coord3]) -> usize {
     }).fold1(|a, s| a.lcm(&s)).unwrap()
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let (mut positions, mut velocities) = parse_input(data)?;
+pub fn parse(data: &str) -> Result<(Vec<Coord3>, Vec<Coord3>)> {
+    parse_input(data)
+}
 
-    let repeat = find_repeat(&positions, &velocities);
-    let energy = {
-        for _ in 0..1000 {
-            step(&mut positions, &mut velocities);
-        }
-        energy(&positions, &velocities)
-    };
+pub fn part1((positions, velocities): &(Vec<Coord3>, Vec<Coord3>)) -> Result<usize> {
+    let (mut positions, mut velocities) = (positions.clone(), velocities.clone());
+    for _ in 0..1000 {
+        step(&mut positions, &mut velocities);
+    }
+    Ok(energy(&positions, &velocities))
+}
 
-    Ok((energy, repeat))
+pub fn part2((positions, velocities): &(Vec<Coord3>, Vec<Coord3>)) -> Result<usize> {
+    Ok(find_repeat(positions, velocities))
 }
 
 #[cfg(test)]
@@ -1,75 +1,33 @@
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
-use crate::intcode::Computer;
+use crate::intcode::{Computer, Device, Program, State};
 use crate::util::Coord;
 
 struct Game {
-    computer: Computer,
     screen: HashMap<Coord, isize>,
     blocks: usize,
     score: usize,
     paddle: Option<isize>,
     ball: Option<(isize, isize)>,
+    pending: Vec<isize>,
 }
 
 impl Game {
-    fn without_coin(code: &[isize]) -> Self {
+    fn new() -> Self {
         Self {
-            computer: Computer::load(code),
             screen: HashMap::<Coord, isize>::new(),
             blocks: 0,
             score: 0,
             paddle: None,
             ball: None,
+            pending: Vec::<isize>::new(),
         }
     }
 
-    fn with_coin(code: &[isize]) -> Self {
-        let mut game = Game::without_coin(code);
-        game.computer.set_control_word(2);
-        game
-    }
-
-    fn draw(&self) {
-        let minx = *self.screen.keys().map(|(x, _)| x).min().unwrap();
-        let maxx = *self.screen.keys().map(|(x, _)| x).max().unwrap();
-        let miny = *self.screen.keys().map(|(_, y)| y).min().unwrap();
-        let maxy = *self.screen.keys().map(|(_, y)| y).max().unwrap();
-        let width = maxx - minx + 1;
-        let height = maxy - miny + 1;
-    
-        let image = (0..height).map(|y| 
-            (0..width).map(|x|
-                match *self.screen.get(&(minx+x, maxy-y)).unwrap_or(&0) { 
-                    1 => "##",
-                    2 => "[]",
-                    3 => "--",
-                    4 => "()",
-                    _ => "  ",
-                }
-            ).fold(String::new(), |s, p| s + p)
-        ).fold(String::new(), |s, l| s + "\n" + &l);
-
-        println!("\n|{}|{}", self.score, image);
-    }
-
-    fn run_step(&mut self) -> Result<bool> {
-        if !self.computer.run_until_output()? {
-            return Ok(false);
-        }
-        let x = self.computer.pop_output()?;
-        if !self.computer.run_until_output()? {
-            return Err(anyhow!("Missing output"));
-        }
-        let y = self.computer.pop_output()?;
-        if !self.computer.run_until_output()? {
-            return Err(anyhow!("Missing output"));
-        }
-        let tile = self.computer.pop_output()?;
-
+    fn update(&mut self, x: isize, y: isize, tile: isize) {
         if (x, y) == (-1, 0) {
             self.score = tile as usize;
-            return Ok(true);
+            return;
         }
         match tile {
             2 => { self.blocks += 1; },
@@ -82,44 +40,50 @@ impl Game {
                 self.blocks -= 1;
             }
         }
-        Ok(true)
     }
-    
-    fn run(&mut self) -> Result<()> {
-        while self.run_step()? {}
-        self.draw();
-        Ok(())
+
+    fn run(&mut self, mut computer: Computer) -> Result<()> {
+        match computer.run_with(self)? {
+            State::Halted if self.pending.is_empty() => Ok(()),
+            State::Halted => Err(anyhow!("Missing output")),
+            State::Fault(fault) => Err(fault.into()),
+            _ => Err(anyhow!("Missing input")),
+        }
     }
+}
 
-    fn play(&mut self) -> Result<()> {
-        loop {
-            if !self.run_step()? {
-                self.draw();
-                return Ok(());
-            }
+impl Device for Game {
+    fn input(&mut self) -> Option<isize> {
+        match (self.paddle, self.ball) {
+            (Some(paddle_x), Some((ball_x, _))) => Some((ball_x - paddle_x).signum()),
+            _ => Some(0),
+        }
+    }
 
-            if let Some(paddle_x) = self.paddle {
-                if let Some((ball_x, _)) = self.ball {
-                    let movement = (ball_x - paddle_x).signum();
-                    self.computer.push_input(movement);
-                    if movement != 0 {
-                        self.paddle = None;
-                    }
-                    self.ball = None;
-                }
-            }
+    fn output(&mut self, value: isize) -> Result<()> {
+        self.pending.push(value);
+        if let [x, y, tile] = self.pending[..] {
+            self.pending.clear();
+            self.update(x, y, tile);
         }
+        Ok(())
     }
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let code = data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?;
-
-    let mut game1 = Game::without_coin(&code);
-    game1.run()?;
+pub fn parse(data: &str) -> Result<Program> {
+    Ok(data.parse::<Program>()?)
+}
 
-    let mut game2 = Game::with_coin(&code);
-    game2.play()?;
+pub fn part1(program: &Program) -> Result<usize> {
+    let mut game = Game::new();
+    game.run(Computer::load(program.code()))?;
+    Ok(game.blocks)
+}
 
-    Ok((game1.blocks, game2.score))
+pub fn part2(program: &Program) -> Result<usize> {
+    let mut game = Game::new();
+    let mut computer = Computer::load(program.code());
+    computer.set_control_word(2);
+    game.run(computer)?;
+    Ok(game.score)
 }
@@ -106,9 +106,16 @@ fn calc_fuel_produced(recipes: &HashMap<String, Recipe>) -> usize {
     fuel
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let recipes = parse_recipes(data)?;
-    Ok((calc_ore_per_fuel(&recipes), calc_fuel_produced(&recipes)))
+pub fn parse(data: &str) -> Result<HashMap<String, Recipe>> {
+    parse_recipes(data)
+}
+
+pub fn part1(recipes: &HashMap<String, Recipe>) -> Result<usize> {
+    Ok(calc_ore_per_fuel(recipes))
+}
+
+pub fn part2(recipes: &HashMap<String, Recipe>) -> Result<usize> {
+    Ok(calc_fuel_produced(recipes))
 }
 
 #[cfg(test)]
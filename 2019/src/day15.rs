@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Error, Result};
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use crate::intcode::Computer;
+use crate::intcode::{Computer, Program};
 use crate::util::{Coord, Dir};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -88,29 +88,6 @@ impl Droid {
         Ok(())
     }
 
-    fn draw(&self) {
-        let minx = *self.map.keys().map(|(x, _)| x).min().unwrap();
-        let maxx = *self.map.keys().map(|(x, _)| x).max().unwrap();
-        let miny = *self.map.keys().map(|(_, y)| y).min().unwrap();
-        let maxy = *self.map.keys().map(|(_, y)| y).max().unwrap();
-        let width = maxx - minx + 1;
-        let height = maxy - miny + 1;
-    
-        let image = (0..height).map(|y| 
-            (0..width).map(|x|
-                match self.map.get(&(minx+x, maxy-y)) { 
-                    Some((Tile::Wall, _)) => "##",
-                    Some((Tile::Room, _)) if self.pos == (minx+x, maxy-y) => "<>",
-                    Some((Tile::Room, _)) => "..",
-                    Some((Tile::Oxygen, _)) => "()",
-                    None => "  ",
-                }
-            ).fold(String::new(), |s, p| s + p)
-        ).fold(String::new(), |s, l| s + "\n" + &l);
-
-        println!("{}", image);
-    }
-
     fn oxygen_distance(&self) -> usize {
         self.map.values().find_map(|(t, p)| if t == &Tile::Oxygen { Some(*p) } else { None }).unwrap()
     }
@@ -135,10 +112,18 @@ impl Droid {
     }
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let code = data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?;
-    let mut droid = Droid::new(&code);
+pub fn parse(data: &str) -> Result<Program> {
+    Ok(data.parse::<Program>()?)
+}
+
+pub fn part1(program: &Program) -> Result<usize> {
+    let mut droid = Droid::new(program.code());
+    droid.traverse()?;
+    Ok(droid.oxygen_distance())
+}
+
+pub fn part2(program: &Program) -> Result<usize> {
+    let mut droid = Droid::new(program.code());
     droid.traverse()?;
-    droid.draw();
-    Ok((droid.oxygen_distance(), droid.oxygen_fill_time()))
+    Ok(droid.oxygen_fill_time())
 }
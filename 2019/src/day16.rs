@@ -67,12 +67,16 @@ fn fft_simple_repeat(input: &[isize], repeat: usize, phases: usize) -> Vec<isize
     output
 }
 
-pub fn solution(data: &str) -> Result<(String, String)> {
-    let input = parse_input(data);
-    Ok((
-        format_output(&fft_phases(&input, 100)), 
-        format_output(&fft_simple_repeat(&input, 10000, 100))
-    ))
+pub fn parse(data: &str) -> Result<Vec<isize>> {
+    Ok(parse_input(data))
+}
+
+pub fn part1(input: &[isize]) -> Result<String> {
+    Ok(format_output(&fft_phases(input, 100)))
+}
+
+pub fn part2(input: &[isize]) -> Result<String> {
+    Ok(format_output(&fft_simple_repeat(input, 10000, 100)))
 }
 
 #[cfg(test)]
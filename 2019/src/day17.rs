@@ -1,5 +1,5 @@
-use anyhow::Result;
-use crate::intcode::Computer;
+use anyhow::{anyhow, Result};
+use crate::intcode::{Computer, Program};
 use crate::util::{Coord, Dir};
 use itertools::Itertools;
 use std::collections::HashMap;
@@ -22,13 +22,9 @@ impl Map {
         let mut pos = None;
 
         computer.run()?;
-        let mut map = Vec::<char>::new();
-        while let Ok(p) = computer.pop_output() {
-            map.push(p as u8 as char);
-        }
-        for line in map.into_iter().collect::<String>().trim().lines().rev() {
+        for line in computer.take_ascii().text.trim().lines() {
             x = 0;
-            for chr in line.trim().chars().rev() {
+            for chr in line.trim().chars() {
                 if chr != '.' {
                     scaffold.insert((x, y), chr != '#');
                     if chr != '#' {
@@ -218,19 +214,27 @@ fn create_programs(source: &[(char, usize)], repeats: &[(usize, Vec<usize>)]) ->
     programs
 }
 
-pub fn solution(data: &str) -> Result<(isize, isize)> {
-    let mut computer = Computer::load(&data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?);
+pub fn parse(data: &str) -> Result<Computer> {
+    Ok(data.parse::<Program>()?.computer())
+}
 
+pub fn part1(computer: &Computer) -> Result<isize> {
+    let map = Map::load(computer.clone())?;
+    Ok(map.intersections().iter().map(|(x, y)| *x * *y).sum())
+}
+
+pub fn part2(computer: &Computer) -> Result<isize> {
     let mut map = Map::load(computer.clone())?;
     let path = map.path();
     let repeats = find_repeats(&path);
     let lines = create_programs(&path, &repeats);
 
+    let mut computer = computer.clone();
     computer.set_control_word(2);
-    for c in (lines.join("\n") + "\nn\n").chars() {
-        computer.push_input(c as isize);
+    for line in &lines {
+        computer.push_line(line);
     }
+    computer.push_line("n");
     computer.run()?;
-
-    Ok((map.intersections().iter().map(|(x, y)| *x * *y).sum(), computer.pop_output()?))
+    computer.take_ascii().result().ok_or_else(|| anyhow!("No dust amount reported"))
 }
\ No newline at end of file
@@ -1,23 +1,41 @@
-use anyhow::Result;
-use crate::intcode::Computer;
+use anyhow::{anyhow, Result};
+use crate::intcode::{Budget, Computer, Program};
+use std::collections::BTreeMap;
 
-fn run_verb_noun(code: &[isize], noun: isize, verb: isize) -> Result<isize> {
-    let mut computer = Computer::load(code);
+fn run_verb_noun(computer: &Computer, noun: isize, verb: isize) -> Result<isize> {
+    let mut computer = computer.clone();
     computer.set_noun_verb(noun, verb);
     computer.run()?;
     Ok(computer.get_result())
 }
 
-pub fn solution(data: &str) -> Result<(isize, isize)> {
-    let code  = data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?;
-    let output = run_verb_noun(&code, 12, 2)?;
+fn find_verb_noun(computer: &Computer, target: isize) -> Result<isize> {
+    let mut symbolic = computer.symbolic();
+    symbolic.set_symbol(1, "noun");
+    symbolic.set_symbol(2, "verb");
+    symbolic.set_budget(Budget::steps(100_000));
+    symbolic.run_to_halt()?;
+    let result = symbolic.result().linear().ok_or_else(|| anyhow!("Result is not linear in noun and verb"))?;
 
     for noun in 0..100 {
-        for verb in 0..100 {
-            if run_verb_noun(&code, noun, verb)? == 19690720 {
-                return Ok((output, 100 * noun + verb))
-            }
+        let known = vec![("noun".to_owned(), noun)].into_iter().collect::<BTreeMap<_, _>>();
+        if let Some(verb) = result.solve_for("verb", target, &known).filter(|v| (0..100).contains(v)) {
+            return Ok(100 * noun + verb);
         }
     }
-    anyhow::bail!("No result")
+    Err(anyhow!("No result"))
+}
+
+pub fn parse(data: &str) -> Result<Computer> {
+    let mut computer = data.parse::<Program>()?.computer();
+    computer.compile();
+    Ok(computer)
+}
+
+pub fn part1(computer: &Computer) -> Result<isize> {
+    run_verb_noun(computer, 12, 2)
+}
+
+pub fn part2(computer: &Computer) -> Result<isize> {
+    find_verb_noun(computer, 19690720)
 }
@@ -65,9 +65,16 @@ fn nearest_crossing(path1: &Path, path2: &Path) -> (usize, usize) {
     (dist, step)
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let paths = data.lines().map(|s| parse_path(s)).collect::<Result<Vec<_>, _>>()?;
-    Ok(nearest_crossing(&paths[0], &paths[1]))
+pub fn parse(data: &str) -> Result<Vec<Path>> {
+    data.lines().map(|s| parse_path(s)).collect::<Result<Vec<_>, _>>()
+}
+
+pub fn part1(paths: &[Path]) -> Result<usize> {
+    Ok(nearest_crossing(&paths[0], &paths[1]).0)
+}
+
+pub fn part2(paths: &[Path]) -> Result<usize> {
+    Ok(nearest_crossing(&paths[0], &paths[1]).1)
 }
 
 #[cfg(test)]
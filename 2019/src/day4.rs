@@ -20,20 +20,24 @@ fn has_strict_double(digits: &[u32; 6]) -> bool {
     (digits[3] != digits[4] && digits[4] == digits[5])
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let limits = data.split('-').map(|s| s.parse::<u32>()).collect::<Result<Vec<_>, _>>()?;
-    
-    Ok((limits[0]..limits[1]+1)
+fn count_passwords(limits: &[u32], rule: fn(&[u32; 6]) -> bool) -> usize {
+    (limits[0]..limits[1]+1)
         .into_iter()
         .map(|n| digits(n))
-        .filter(|d| not_decrease(d))
-        .fold((0usize, 0usize), 
-            |(c1, c2), d| (
-                if has_double(&d) { c1 + 1 } else { c1 },
-                if has_strict_double(&d) { c2 + 1 } else { c2 }
-            )
-        )
-    )
+        .filter(|d| not_decrease(d) && rule(d))
+        .count()
+}
+
+pub fn parse(data: &str) -> Result<Vec<u32>> {
+    Ok(data.split('-').map(|s| s.parse::<u32>()).collect::<Result<Vec<_>, _>>()?)
+}
+
+pub fn part1(limits: &[u32]) -> Result<usize> {
+    Ok(count_passwords(limits, has_double))
+}
+
+pub fn part2(limits: &[u32]) -> Result<usize> {
+    Ok(count_passwords(limits, has_strict_double))
 }
 
 #[cfg(test)]
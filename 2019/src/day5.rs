@@ -1,5 +1,5 @@
 use anyhow::Result;
-use crate::intcode::Computer;
+use crate::intcode::{Computer, Program};
 
 fn run_input(code: &[isize], input: isize) -> Result<isize> {
     let mut computer = Computer::load(code);
@@ -8,7 +8,14 @@ fn run_input(code: &[isize], input: isize) -> Result<isize> {
     computer.pop_output()
 }
 
-pub fn solution(data: &str) -> Result<(isize, isize)> {
-    let code  = data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?;
-    Ok((run_input(&code, 1)?, run_input(&code, 5)?))
+pub fn parse(data: &str) -> Result<Program> {
+    Ok(data.parse::<Program>()?)
+}
+
+pub fn part1(program: &Program) -> Result<isize> {
+    run_input(program.code(), 1)
+}
+
+pub fn part2(program: &Program) -> Result<isize> {
+    run_input(program.code(), 5)
 }
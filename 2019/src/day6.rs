@@ -1,23 +1,23 @@
 use anyhow::Result;
 use std::collections::HashMap;
 
-fn parse_map(data: &str) -> HashMap<&str, &str> {
-    data.lines().fold(HashMap::<&str, &str>::new(), |mut h, line| { 
+fn parse_map(data: &str) -> HashMap<String, String> {
+    data.lines().fold(HashMap::<String, String>::new(), |mut h, line| { 
         let parts = line.split(')').collect::<Vec<&str>>();
-        h.insert(parts[1], parts[0]);
+        h.insert(parts[1].to_string(), parts[0].to_string());
         h
     })
 }
 
-fn route_to_com<'a>(orbits: &'a HashMap::<&str, &str>, start: &'a str) -> Vec<&'a str> {
+fn route_to_com<'a>(orbits: &'a HashMap::<String, String>, start: &'a str) -> Vec<&'a str> {
     let mut route = Vec::<&str>::new();
-    while let Some(c) = orbits.get(route.last().unwrap_or(&start)) {
+    while let Some(c) = orbits.get(*route.last().unwrap_or(&start)) {
         route.push(c);
     }
     route
 }
 
-fn route_between<'a>(orbits: &'a HashMap::<&str, &str>, start: &'a str, end: &'a str) -> Vec<&'a str> {
+fn route_between<'a>(orbits: &'a HashMap::<String, String>, start: &'a str, end: &'a str) -> Vec<&'a str> {
     let mut route1 = route_to_com(orbits, start);
     let mut route2 = route_to_com(orbits, end);
 
@@ -44,9 +44,16 @@ fn route_between<'a>(orbits: &'a HashMap::<&str, &str>, start: &'a str, end: &'a
     route1
 }
 
-pub fn solution(data: &str) -> Result<(usize, usize)> {
-    let orbits = parse_map(data);
-    Ok((orbits.keys().map(|k| route_to_com(&orbits, k).len()).sum(), route_between(&orbits, "YOU", "SAN").len()-1))
+pub fn parse(data: &str) -> Result<HashMap<String, String>> {
+    Ok(parse_map(data))
+}
+
+pub fn part1(orbits: &HashMap<String, String>) -> Result<usize> {
+    Ok(orbits.keys().map(|k| route_to_com(orbits, k).len()).sum())
+}
+
+pub fn part2(orbits: &HashMap<String, String>) -> Result<usize> {
+    Ok(route_between(orbits, "YOU", "SAN").len()-1)
 }
 
 #[cfg(test)]
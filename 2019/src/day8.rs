@@ -1,7 +1,11 @@
 use anyhow::Result;
 use itertools::Itertools;
 
-pub fn solution(data: &str) -> Result<(usize, String)> {
+pub fn parse(data: &str) -> Result<String> {
+    Ok(data.to_string())
+}
+
+pub fn part1(data: &str) -> Result<usize> {
     let layer_size = 25 * 6;
 
     let check_layer = data.chars()
@@ -14,9 +18,15 @@ pub fn solution(data: &str) -> Result<(usize, String)> {
             }
         ))
         .min_by_key(|ls| ls[0]).unwrap();
-    
+
+    Ok(check_layer[1] * check_layer[2])
+}
+
+pub fn part2(data: &str) -> Result<String> {
+    let layer_size = 25 * 6;
+
     let image = (0..layer_size).map(|i| data.chars().skip(i).step_by(layer_size).fold('2', |p, c| if p == '2' { c } else { p }))
         .map(|p| match p { '1' => "##", _ => "  " }).chunks(25).into_iter().map(|l| l.collect::<String>()).fold(String::new(), |s, l| s + "\n" + &l);
-    
-    Ok((check_layer[1] * check_layer[2], image))
+
+    Ok(image)
 }
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use crate::intcode::Computer;
+use crate::intcode::{Computer, Program};
 
 fn run_program(computer: &Computer, program: isize) -> Result<isize> {
     let mut c = computer.clone();
@@ -8,7 +8,14 @@ fn run_program(computer: &Computer, program: isize) -> Result<isize> {
     c.pop_output()
 }
 
-pub fn solution(data: &str) -> Result<(isize, isize)> {
-    let computer = Computer::load(&data.split(',').map(|s| s.parse::<isize>()).collect::<Result<Vec<_>,_>>()?);
-    Ok((run_program(&computer, 1)?, run_program(&computer, 2)?))
+pub fn parse(data: &str) -> Result<Computer> {
+    Ok(data.parse::<Program>()?.computer())
+}
+
+pub fn part1(computer: &Computer) -> Result<isize> {
+    run_program(computer, 1)
+}
+
+pub fn part2(computer: &Computer) -> Result<isize> {
+    run_program(computer, 2)
 }
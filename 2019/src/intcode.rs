@@ -1,34 +1,248 @@
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, Error, Result};
+use num::BigInt;
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+mod arith;
+mod ascii;
+mod asm;
+mod budget;
+mod cfg;
+mod circuit;
+mod cluster;
+mod codemap;
+mod compile;
+mod debug;
+mod device;
+mod disasm;
+mod fuzz;
+mod memory;
+mod profile;
+mod program;
+mod snapshot;
+mod symbolic;
+mod trace;
+
+pub use arith::Arithmetic;
+pub use ascii::{AsciiOutput, Terminal};
+pub use asm::{assemble, AsmError};
+pub use budget::Budget;
+pub use cfg::{Block, ControlFlow, Exit, Function};
+pub use circuit::{Circuit, Node};
+pub use cluster::{Cluster, Packet, Round, Schedule};
+pub use codemap::{CodeMap, CodeWrite, Region};
+pub use debug::{Breakpoint, Debugger, Stop};
+pub use device::{Device, Queues};
+pub use disasm::{Item, Line, Listing};
+pub use fuzz::{check, fuzz, generate, minimize, reference, Case, End, Mismatch, Outcome, Rng};
+pub use memory::MemoryModel;
+pub use profile::Profile;
+pub use program::{Program, ProgramError};
+pub use snapshot::Snapshot;
+pub use symbolic::{Expr, Linear, Symbolic};
+pub use trace::{Divergence, Trace, TraceStep};
+
+use compile::Compiled;
+use memory::Pages;
 
 #[derive(Debug, Clone)]
 pub struct Computer {
     memory: Vec<isize>,
+    pages: Option<Pages>,
+    wide: BTreeMap<usize, BigInt>,
+    arithmetic: Arithmetic,
     ip: usize,
     base: isize,
-    input: Vec<isize>,
+    input: VecDeque<isize>,
     output: Vec<isize>,
+    trace: Option<Trace>,
+    compiled: Option<Compiled>,
+    code_map: Option<CodeMap>,
+    profile: Option<Profile>,
+    budget: Option<Budget>,
 }
 
-#[derive(Debug, PartialEq)]
-enum Param<'a> {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Halted,
+    NeedsInput,
+    Output(isize),
+    Fault(Fault),
+    BudgetExhausted,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultKind {
+    InvalidOpcode(isize),
+    InvalidMode(isize),
+    NegativeAddress(isize),
+    ProgramOverrun,
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fault {
+    pub ip: usize,
+    pub kind: FaultKind,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            FaultKind::InvalidOpcode(op) => write!(f, "Invalid opcode {} at {}", op, self.ip),
+            FaultKind::InvalidMode(mode) => write!(f, "Invalid parameter mode {} at {}", mode, self.ip),
+            FaultKind::NegativeAddress(addr) => write!(f, "Negative address {} at {}", addr, self.ip),
+            FaultKind::ProgramOverrun => write!(f, "Program overrun at {}", self.ip),
+            FaultKind::Overflow => write!(f, "Arithmetic overflow at {}", self.ip),
+        }
+    }
+}
+
+impl std::error::Error for Fault {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Opcode {
+    Add = 1,
+    Mul = 2,
+    In = 3,
+    Out = 4,
+    Jt = 5,
+    Jf = 6,
+    Lt = 7,
+    Eq = 8,
+    Arb = 9,
+    Hlt = 99,
+}
+
+impl Opcode {
+    pub fn params(&self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Mul | Opcode::Lt | Opcode::Eq => 3,
+            Opcode::Jt | Opcode::Jf => 2,
+            Opcode::In | Opcode::Out | Opcode::Arb => 1,
+            Opcode::Hlt => 0,
+        }
+    }
+
+    pub fn output_param(&self) -> Option<u32> {
+        match self {
+            Opcode::Add | Opcode::Mul | Opcode::Lt | Opcode::Eq => Some(3),
+            Opcode::In => Some(1),
+            _ => None,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Mul => "mul",
+            Opcode::In => "in",
+            Opcode::Out => "out",
+            Opcode::Jt => "jt",
+            Opcode::Jf => "jf",
+            Opcode::Lt => "lt",
+            Opcode::Eq => "eq",
+            Opcode::Arb => "arb",
+            Opcode::Hlt => "hlt",
+        }
+    }
+}
+
+impl TryFrom<isize> for Opcode {
+    type Error = Error;
+
+    fn try_from(data: isize) -> Result<Self> {
+        match data {
+            1 => Ok(Opcode::Add),
+            2 => Ok(Opcode::Mul),
+            3 => Ok(Opcode::In),
+            4 => Ok(Opcode::Out),
+            5 => Ok(Opcode::Jt),
+            6 => Ok(Opcode::Jf),
+            7 => Ok(Opcode::Lt),
+            8 => Ok(Opcode::Eq),
+            9 => Ok(Opcode::Arb),
+            99 => Ok(Opcode::Hlt),
+            _ => Err(anyhow!("Invalid opcode")),
+        }
+    }
+}
+
+impl FromStr for Opcode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "add" => Ok(Opcode::Add),
+            "mul" => Ok(Opcode::Mul),
+            "in" => Ok(Opcode::In),
+            "out" => Ok(Opcode::Out),
+            "jt" => Ok(Opcode::Jt),
+            "jf" => Ok(Opcode::Jf),
+            "lt" => Ok(Opcode::Lt),
+            "eq" => Ok(Opcode::Eq),
+            "arb" => Ok(Opcode::Arb),
+            "hlt" => Ok(Opcode::Hlt),
+            _ => Err(anyhow!("Invalid mnemonic")),
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.mnemonic())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Param<'a> {
     Immediate(&'a isize),
     Position(&'a isize),
     Relative(&'a isize),
 }
 
+impl fmt::Display for Param<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Immediate(v) => write!(f, "#{}", v),
+            Param::Position(v) => write!(f, "{}", v),
+            Param::Relative(v) => write!(f, "rel{:+}", v),
+        }
+    }
+}
+
 impl Computer {
     pub fn load(code: &[isize]) -> Self {
+        Computer::load_with(code, MemoryModel::Dense)
+    }
+
+    pub fn load_with(code: &[isize], model: MemoryModel) -> Self {
         Computer { 
             memory: code.to_owned(),
+            pages: match model {
+                MemoryModel::Dense => None,
+                MemoryModel::Paged => Some(Pages::default()),
+            },
+            wide: BTreeMap::new(),
+            arithmetic: Arithmetic::Checked,
             ip: 0,
             base: 0,
-            input: Vec::new(),
+            input: VecDeque::new(),
             output: Vec::new(),
+            trace: None,
+            compiled: None,
+            code_map: None,
+            profile: None,
+            budget: None,
         }
     }
 
     pub fn push_input(&mut self, data: isize) {
-        self.input.push(data);
+        self.input.push_back(data);
     }
 
     pub fn pop_output(&mut self) -> Result<isize> {
@@ -38,120 +252,301 @@ impl Computer {
     pub fn set_noun_verb(&mut self, noun: isize, verb: isize) {
         self.memory[1] = noun;
         self.memory[2] = verb;
+        self.invalidate(1);
+        self.invalidate(2);
     }    
     
     pub fn set_control_word(&mut self, data: isize) {
         self.memory[0] = data;
+        self.invalidate(0);
     }
 
     pub fn get_result(&self) -> isize {
         self.memory[0]
     }
-    
-    fn current_opcode(&self) -> Result<isize> {
-        ensure!(self.ip < self.memory.len(), "Program overrun");
-        Ok(self.memory[self.ip] % 100)
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn base(&self) -> isize {
+        self.base
+    }
+
+    pub fn input(&self) -> &VecDeque<isize> {
+        &self.input
+    }
+
+    pub fn output(&self) -> &[isize] {
+        &self.output
+    }
+
+    pub fn memory_len(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn memory_model(&self) -> MemoryModel {
+        match self.pages {
+            Some(_) => MemoryModel::Paged,
+            None => MemoryModel::Dense,
+        }
+    }
+
+    pub fn peek(&self, addr: usize) -> isize {
+        self.cell(addr).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    fn invalidate(&mut self, addr: usize) {
+        if let Some(compiled) = self.compiled.as_mut() {
+            compiled.invalidate(addr);
+        }
+    }
+
+    #[inline]
+    fn cell(&self, addr: usize) -> Option<&isize> {
+        match (self.memory.get(addr), &self.pages) {
+            (Some(value), _) => Some(value),
+            (None, Some(pages)) => pages.get(addr),
+            (None, None) => None,
+        }
     }
     
-    fn mem(&self, pos: isize) -> Result<&isize> {
-        ensure!(pos >= 0, "Negative address");
+    fn fault(&self, kind: FaultKind) -> Fault {
+        Fault { ip: self.ip, kind }
+    }
+
+    fn opcode_at(&self, addr: usize) -> Result<Opcode, Fault> {
+        let fault = |kind| Fault { ip: addr, kind };
+        let op = self.cell(addr).ok_or_else(|| fault(FaultKind::ProgramOverrun))? % 100;
+        Opcode::try_from(op).map_err(|_| fault(FaultKind::InvalidOpcode(op)))
+    }
 
-        if pos as usize >= self.memory.len() { 
-            Ok(&0) 
-        } else { 
-            Ok(&self.memory[pos as usize]) 
+    fn current_opcode(&self) -> Result<Opcode, Fault> {
+        self.opcode_at(self.ip)
+    }
+    
+    #[inline]
+    fn mem(&self, pos: isize) -> Result<&isize, Fault> {
+        if pos < 0 {
+            return Err(self.fault(FaultKind::NegativeAddress(pos)));
         }
+
+        Ok(self.cell(pos as usize).unwrap_or(&0))
     }
 
-    fn mem_mut(&mut self, pos: isize) -> Result<&mut isize> {
-        ensure!(pos >= 0, "Negative address");
+    #[inline]
+    fn mem_mut(&mut self, pos: isize) -> Result<&mut isize, Fault> {
+        if pos < 0 {
+            return Err(self.fault(FaultKind::NegativeAddress(pos)));
+        }
 
-        if pos as usize >= self.memory.len() {
-            self.memory.resize(pos as usize + 1, 0);
+        let pos = pos as usize;
+        if !self.wide.is_empty() {
+            self.wide.remove(&pos);
         }
-        Ok(&mut self.memory[pos as usize])
+        self.invalidate(pos);
+        if pos >= self.memory.len() {
+            match self.pages.as_mut() {
+                Some(pages) => return Ok(pages.get_mut(pos)),
+                None => self.memory.resize(pos + 1, 0),
+            }
+        }
+        Ok(&mut self.memory[pos])
     }
 
-    fn param_mode(&self, idx: u32) -> Result<Param> {
-        let value = self.memory.get(self.ip + (idx as usize)).ok_or(anyhow!("Missing parameter"))?;
+    fn param_mode_at(&self, addr: usize, idx: u32) -> Result<Param<'_>, Fault> {
+        let fault = |kind| Fault { ip: addr, kind };
+        let op = self.cell(addr).ok_or_else(|| fault(FaultKind::ProgramOverrun))?;
+        let value = self.cell(addr + (idx as usize)).ok_or_else(|| fault(FaultKind::ProgramOverrun))?;
 
-        match self.memory[self.ip] / 10isize.pow(idx + 1) % 10 {
+        match op / 10isize.pow(idx + 1) % 10 {
             0 => Ok(Param::Position(value)),
             1 => Ok(Param::Immediate(value)),
             2 => Ok(Param::Relative(value)),
-            _ => return Err(anyhow!("Invalid parameter mode"))
+            mode => Err(fault(FaultKind::InvalidMode(mode))),
         }
     }
 
-    fn param(&self, idx: u32) -> Result<&isize> {
+    fn param_mode(&self, idx: u32) -> Result<Param<'_>, Fault> {
+        self.param_mode_at(self.ip, idx)
+    }
+
+    fn param(&self, idx: u32) -> Result<&isize, Fault> {
         match self.param_mode(idx)? {
             Param::Immediate(v) => Ok(v),
             Param::Position(v) => self.mem(*v),
-            Param::Relative(v) => self.mem(self.base + *v),
+            Param::Relative(v) => self.mem(self.relative(*v)?),
         }
     }
     
-    fn param_mut(&mut self, idx: u32) -> Result<&mut isize> {
+    fn param_mut(&mut self, idx: u32) -> Result<&mut isize, Fault> {
         match self.param_mode(idx)? {
-            Param::Immediate(_) => Err(anyhow!("Illegal parameter mode")),
+            Param::Immediate(_) => Err(self.fault(FaultKind::InvalidMode(1))),
             Param::Position(v) => {
                 let p = *v;
                 self.mem_mut(p)
             }
             Param::Relative(v) => {
-                let p = self.base + *v;
+                let p = self.relative(*v)?;
                 self.mem_mut(p)
             },
         }
     }
 
-    pub fn run_single_step(&mut self) -> Result<bool> {
+    fn write_target(&self) -> Result<Option<isize>, Fault> {
+        match self.current_opcode()?.output_param() {
+            Some(idx) => match self.param_mode(idx)? {
+                Param::Position(v) => Ok(Some(*v)),
+                Param::Relative(v) => Ok(Some(self.relative(*v)?)),
+                Param::Immediate(_) => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn step(&mut self, device: &mut dyn Device) -> Result<Option<State>> {
+        if let Some(state) = self.budget_interrupt() {
+            return Ok(Some(state));
+        }
+        let traced = match self.trace {
+            Some(_) => self.trace_begin().ok(),
+            None => None,
+        };
+        let code = match self.code_map {
+            Some(_) => Some((self.ip, self.code_begin())),
+            None => None,
+        };
+        let sample = match self.profile {
+            Some(_) => self.profile_begin(),
+            None => None,
+        };
+        let result = if self.can_run_compiled() {
+            self.execute_compiled(device)
+        } else {
+            self.execute(device)
+        };
+        let result = match result {
+            Err(e) => e.downcast::<Fault>().map(|fault| Some(State::Fault(fault))),
+            result => result,
+        };
+        if let (Some(step), Ok(None)) | (Some(step), Ok(Some(State::Halted))) = (traced, &result) {
+            self.trace_end(step);
+        }
+        if let (Some((ip, target)), Ok(None)) = (code, &result) {
+            self.code_end(ip, target);
+        }
+        if let (Some(sample), Ok(None)) | (Some(sample), Ok(Some(State::Halted))) = (sample, &result) {
+            self.profile_end(sample);
+        }
+        if let Ok(None) = result {
+            self.charge_budget();
+        }
+        result
+    }
+
+    fn execute(&mut self, device: &mut dyn Device) -> Result<Option<State>> {
         self.ip += match self.current_opcode()? {
-            1 => {
-                *self.param_mut(3)? = *self.param(1)? + *self.param(2)?;
+            Opcode::Add => {
+                self.binary(arith::ADD)?;
                 4
             },
-            2 => { 
-                *self.param_mut(3)? = *self.param(1)? * *self.param(2)?;
+            Opcode::Mul => { 
+                self.binary(arith::MUL)?;
                 4
             },
-            3 => { 
-                *self.param_mut(1)? = *self.input.first().ok_or(anyhow!("Empty input"))?;
-                self.input.remove(0);
+            Opcode::In => {
+                let value = match device.input() {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                *self.param_mut(1)? = value;
                 2
             },
-            4 => { 
-                self.output.push(*self.param(1)?);
+            Opcode::Out => {
+                device.output(self.word(1)?)?;
                 2
             },
-            5 => if *self.param(1)? != 0 {
-                self.ip = *self.param(2)? as usize;
+            Opcode::Jt => if !self.is_zero(1)? {
+                self.ip = self.word(2)? as usize;
                 0
             } else {
                 3
             },
-            6 => if *self.param(1)? == 0 {
-                self.ip = *self.param(2)? as usize;
+            Opcode::Jf => if self.is_zero(1)? {
+                self.ip = self.word(2)? as usize;
                 0
             } else {
                 3
             },
-            7 => {
-                *self.param_mut(3)? = if *self.param(1)? < *self.param(2)? { 1 } else { 0 };
+            Opcode::Lt => {
+                self.binary(arith::LT)?;
                 4
             },
-            8 => {
-                *self.param_mut(3)? = if *self.param(1)? == *self.param(2)? { 1 } else { 0 };
+            Opcode::Eq => {
+                self.binary(arith::EQ)?;
                 4
             },
-            9 => {
-                self.base += *self.param(1)?;
+            Opcode::Arb => {
+                self.adjust_base()?;
                 2
             },
-            99 => return Ok(false),
-            _ => return Err(anyhow!("Invalid opcode"))
+            Opcode::Hlt => return Ok(Some(State::Halted)),
         };
-        Ok(true)
+        Ok(None)
+    }
+
+    fn with_queues<T, F>(&mut self, f: F) -> T
+        where F: FnOnce(&mut Self, &mut Queues) -> Result<T>
+    {
+        let mut queues = Queues { input: mem::take(&mut self.input), output: mem::take(&mut self.output) };
+        let result = f(self, &mut queues).expect("Queues never fail");
+        self.input = queues.input;
+        self.output = queues.output;
+        result
+    }
+
+    fn step_queued(&mut self) -> Option<State> {
+        self.with_queues(|c, queues| c.step(queues))
+    }
+
+    pub fn run_single_step(&mut self) -> Result<bool> {
+        match self.step_queued() {
+            None | Some(State::Output(_)) => Ok(true),
+            Some(State::Halted) => Ok(false),
+            Some(State::NeedsInput) => Err(anyhow!("Empty input")),
+            Some(State::Fault(fault)) => Err(fault.into()),
+            Some(State::BudgetExhausted) => Err(anyhow!("Budget exhausted")),
+            Some(State::Stopped) => Err(anyhow!("Stopped")),
+        }
+    }
+
+    pub fn run_with(&mut self, device: &mut dyn Device) -> Result<State> {
+        if self.can_run_compiled() {
+            return self.run_compiled(device);
+        }
+        loop {
+            if let Some(state) = self.step(device)? {
+                return Ok(state);
+            }
+        }
+    }
+
+    pub fn resume(&mut self) -> State {
+        self.with_queues(|c, queues| c.run_with(queues))
+    }
+
+    pub fn run_until_event(&mut self) -> State {
+        self.with_queues(|c, queues| {
+            let output_len = queues.output.len();
+            loop {
+                match c.step(queues)? {
+                    Some(state) => return Ok(state),
+                    None if queues.output.len() > output_len => return Ok(State::Output(queues.output.pop().unwrap())),
+                    None => {},
+                }
+            }
+        })
     }
 
     pub fn run_until_output(&mut self) -> Result<bool> {
@@ -165,9 +560,14 @@ impl Computer {
     }
 
     pub fn run(&mut self) -> Result<()> {
-        while self.run_single_step()? {
+        match self.resume() {
+            State::Halted => Ok(()),
+            State::NeedsInput => Err(anyhow!("Empty input")),
+            State::Fault(fault) => Err(fault.into()),
+            State::BudgetExhausted => Err(anyhow!("Budget exhausted")),
+            State::Stopped => Err(anyhow!("Stopped")),
+            State::Output(_) => unreachable!("Queued output never interrupts"),
         }
-        Ok(())
     }
 }
 
@@ -364,17 +764,69 @@ mod test {
 
     #[test]
     fn large_num_and_mem_support() {
-        let quine = [109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
-        let mut c = Computer::load(&quine);
-        assert!(c.run().is_ok());
-        assert_eq!(&c.output, &quine);
+        for arithmetic in [Arithmetic::Checked, Arithmetic::Wrapping, Arithmetic::BigInt].iter() {
+            let quine = [109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+            let mut c = Computer::load(&quine);
+            c.set_arithmetic(*arithmetic);
+            assert!(c.run().is_ok());
+            assert_eq!(&c.output, &quine);
+
+            c = Computer::load(&[1102,34915192,34915192,7,4,7,99,0]);
+            c.set_arithmetic(*arithmetic);
+            assert!(c.run().is_ok());
+            assert_eq!(c.pop_output().unwrap().to_string().len(), 16);
+
+            c = Computer::load(&[104,1125899906842624,99]);
+            c.set_arithmetic(*arithmetic);
+            assert!(c.run().is_ok());
+            assert_eq!(c.pop_output().unwrap(), 1125899906842624);
+
+            c = Computer::load(&[1102,3037000500,3037000500,7,4,7,99,0]);
+            c.set_arithmetic(*arithmetic);
+            match arithmetic {
+                Arithmetic::Checked => assert_eq!(c.run().unwrap_err().to_string(), "Arithmetic overflow at 0"),
+                Arithmetic::Wrapping => {
+                    assert!(c.run().is_ok());
+                    assert_eq!(c.pop_output().unwrap(), 3037000500isize.wrapping_mul(3037000500));
+                },
+                Arithmetic::BigInt => {
+                    assert_eq!(c.run().unwrap_err().to_string(), "Arithmetic overflow at 4");
+                    assert_eq!(c.peek_wide(7), BigInt::from(3037000500isize) * 3037000500isize);
+                },
+            }
+        }
+    }
 
-        c = Computer::load(&[1102,34915192,34915192,7,4,7,99,0]);
-        assert!(c.run().is_ok());
-        assert_eq!(c.pop_output().unwrap().to_string().len(), 16);
+    #[test]
+    fn run_until_event() {
+        let mut c = Computer::load(&[3,11,1001,11,1,12,4,12,4,11,99,0,0]);
+        assert_eq!(c.run_until_event(), State::NeedsInput);
+        c.push_input(5);
+        assert_eq!(c.run_until_event(), State::Output(6));
+        assert_eq!(c.run_until_event(), State::Output(5));
+        assert!(c.output.is_empty());
+        assert_eq!(c.run_until_event(), State::Halted);
+        assert_eq!(c.run_until_event(), State::Halted);
+    }
 
-        c = Computer::load(&[104,1125899906842624,99]);
-        assert!(c.run().is_ok());
-        assert_eq!(c.pop_output().unwrap(), 1125899906842624);
+    #[test]
+    fn faults() {
+        let fault = |code: &[isize]| match Computer::load(code).run_until_event() {
+            State::Fault(fault) => Some(fault),
+            _ => None,
+        };
+        assert_eq!(fault(&[1,0,0,0,42]), Some(Fault { ip: 4, kind: FaultKind::InvalidOpcode(42) }));
+        assert_eq!(fault(&[1,0,0,0]), Some(Fault { ip: 4, kind: FaultKind::ProgramOverrun }));
+        assert_eq!(fault(&[1101,1,1,0,1001]), Some(Fault { ip: 4, kind: FaultKind::ProgramOverrun }));
+        assert_eq!(fault(&[1301,0,0,0,99]), Some(Fault { ip: 0, kind: FaultKind::InvalidMode(3) }));
+        assert_eq!(fault(&[11101,0,0,0,99]), Some(Fault { ip: 0, kind: FaultKind::InvalidMode(1) }));
+        assert_eq!(fault(&[1101,0,0,0,4,-3]), Some(Fault { ip: 4, kind: FaultKind::NegativeAddress(-3) }));
+        assert_eq!(fault(&[109,-5,204,2,99]), Some(Fault { ip: 2, kind: FaultKind::NegativeAddress(-3) }));
+        assert_eq!(fault(&[99]), None);
+
+        let mut c = Computer::load(&[2,0,0,0,77]);
+        let e = c.run().unwrap_err();
+        assert_eq!(e.to_string(), "Invalid opcode 77 at 4");
+        assert_eq!(e.downcast::<Fault>().unwrap().kind, FaultKind::InvalidOpcode(77));
     }
 }
\ No newline at end of file
@@ -0,0 +1,226 @@
+use num::{BigInt, Integer, ToPrimitive, Zero};
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{anyhow, Error, Result};
+use super::{Computer, Fault, FaultKind, Param};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arithmetic {
+    Checked,
+    Wrapping,
+    BigInt,
+}
+
+impl fmt::Display for Arithmetic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arithmetic::Checked => write!(f, "checked"),
+            Arithmetic::Wrapping => write!(f, "wrapping"),
+            Arithmetic::BigInt => write!(f, "bigint"),
+        }
+    }
+}
+
+impl FromStr for Arithmetic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "checked" => Ok(Arithmetic::Checked),
+            "wrapping" => Ok(Arithmetic::Wrapping),
+            "bigint" => Ok(Arithmetic::BigInt),
+            _ => Err(anyhow!("Unknown arithmetic `{}`", s)),
+        }
+    }
+}
+
+pub(super) struct BinaryOp {
+    pub checked: fn(isize, isize) -> Option<isize>,
+    pub wrapping: fn(isize, isize) -> isize,
+    pub wide: fn(&BigInt, &BigInt) -> BigInt,
+}
+
+pub(super) const ADD: BinaryOp = BinaryOp {
+    checked: isize::checked_add,
+    wrapping: isize::wrapping_add,
+    wide: |a, b| a + b,
+};
+
+pub(super) const MUL: BinaryOp = BinaryOp {
+    checked: isize::checked_mul,
+    wrapping: isize::wrapping_mul,
+    wide: |a, b| a * b,
+};
+
+pub(super) const LT: BinaryOp = BinaryOp {
+    checked: |a, b| Some((a < b) as isize),
+    wrapping: |a, b| (a < b) as isize,
+    wide: |a, b| BigInt::from((a < b) as isize),
+};
+
+pub(super) const EQ: BinaryOp = BinaryOp {
+    checked: |a, b| Some((a == b) as isize),
+    wrapping: |a, b| (a == b) as isize,
+    wide: |a, b| BigInt::from((a == b) as isize),
+};
+
+fn wrap(value: &BigInt) -> isize {
+    let modulus = BigInt::from(usize::MAX) + 1;
+    value.mod_floor(&modulus).to_usize().unwrap() as isize
+}
+
+impl Computer {
+    pub fn arithmetic(&self) -> Arithmetic {
+        self.arithmetic
+    }
+
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.arithmetic = arithmetic;
+    }
+
+    pub fn peek_wide(&self, addr: usize) -> BigInt {
+        match self.wide.get(&addr) {
+            Some(value) => value.clone(),
+            None => BigInt::from(self.peek(addr)),
+        }
+    }
+
+    fn param_addr(&self, idx: u32) -> Result<Option<usize>, Fault> {
+        match self.param_mode(idx)? {
+            Param::Immediate(_) => Ok(None),
+            Param::Position(v) => Ok(Some(*v as usize)),
+            Param::Relative(v) => Ok(Some(self.relative(*v)? as usize)),
+        }
+    }
+
+    fn wide_param(&self, idx: u32) -> Result<BigInt, Fault> {
+        let value = *self.param(idx)?;
+        match self.param_addr(idx)?.and_then(|addr| self.wide.get(&addr)) {
+            Some(wide) => Ok(wide.clone()),
+            None => Ok(BigInt::from(value)),
+        }
+    }
+
+    pub(super) fn word(&self, idx: u32) -> Result<isize, Fault> {
+        let value = *self.param(idx)?;
+        if !self.wide.is_empty() && self.param_addr(idx)?.is_some_and(|addr| self.wide.contains_key(&addr)) {
+            return Err(self.fault(FaultKind::Overflow));
+        }
+        Ok(value)
+    }
+
+    pub(super) fn is_zero(&self, idx: u32) -> Result<bool, Fault> {
+        match self.arithmetic {
+            Arithmetic::BigInt => Ok(self.wide_param(idx)?.is_zero()),
+            _ => Ok(*self.param(idx)? == 0),
+        }
+    }
+
+    pub(super) fn binary(&mut self, op: BinaryOp) -> Result<(), Fault> {
+        let result = match self.arithmetic {
+            Arithmetic::Checked => (op.checked)(*self.param(1)?, *self.param(2)?)
+                .ok_or_else(|| self.fault(FaultKind::Overflow))?,
+            Arithmetic::Wrapping => (op.wrapping)(*self.param(1)?, *self.param(2)?),
+            Arithmetic::BigInt => {
+                let value = (op.wide)(&self.wide_param(1)?, &self.wide_param(2)?);
+                if let Some(result) = value.to_isize() {
+                    result
+                } else {
+                    *self.param_mut(3)? = wrap(&value);
+                    let addr = self.param_addr(3)?.unwrap();
+                    self.wide.insert(addr, value);
+                    return Ok(());
+                }
+            },
+        };
+        *self.param_mut(3)? = result;
+        Ok(())
+    }
+
+    #[inline]
+    pub(super) fn relative(&self, offset: isize) -> Result<isize, Fault> {
+        match self.arithmetic {
+            Arithmetic::Wrapping => Ok(self.base.wrapping_add(offset)),
+            _ => self.base.checked_add(offset).ok_or_else(|| self.fault(FaultKind::Overflow)),
+        }
+    }
+
+    pub(super) fn adjust_base(&mut self) -> Result<(), Fault> {
+        let offset = self.word(1)?;
+        self.base = match self.arithmetic {
+            Arithmetic::Wrapping => self.base.wrapping_add(offset),
+            _ => self.base.checked_add(offset).ok_or_else(|| self.fault(FaultKind::Overflow))?,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::State;
+
+    const SQUARE_TWICE: [isize; 10] = [2,9,9,9,2,9,9,9,99,4294967296];
+
+    fn run(code: &[isize], arithmetic: Arithmetic) -> Computer {
+        let mut c = Computer::load(code);
+        c.set_arithmetic(arithmetic);
+        c.resume();
+        c
+    }
+
+    #[test]
+    fn checked() {
+        let mut c = Computer::load(&SQUARE_TWICE);
+        assert_eq!(c.arithmetic(), Arithmetic::Checked);
+        assert_eq!(c.resume(), State::Fault(Fault { ip: 0, kind: FaultKind::Overflow }));
+        assert_eq!(c.run().unwrap_err().to_string(), "Arithmetic overflow at 0");
+
+        let c = run(&[1101,9223372036854775807,-1,0,99], Arithmetic::Checked);
+        assert_eq!(c.peek(0), isize::MAX - 1);
+        let mut c = Computer::load(&[109,9223372036854775807,109,1,99]);
+        assert_eq!(c.resume(), State::Fault(Fault { ip: 2, kind: FaultKind::Overflow }));
+    }
+
+    #[test]
+    fn wrapping() {
+        let c = run(&SQUARE_TWICE, Arithmetic::Wrapping);
+        assert_eq!(c.ip(), 8);
+        assert_eq!(c.peek(9), 0);
+        let c = run(&[1101,9223372036854775807,1,0,99], Arithmetic::Wrapping);
+        assert_eq!(c.peek(0), isize::MIN);
+    }
+
+    #[test]
+    fn bigint() {
+        let c = run(&SQUARE_TWICE, Arithmetic::BigInt);
+        assert_eq!(c.ip(), 8);
+        assert_eq!(c.peek(9), 0);
+        assert_eq!(c.peek_wide(9), BigInt::from(1) << 128);
+        assert_eq!(c.peek_wide(0), BigInt::from(2));
+
+        let code = [2,14,14,15,1,15,16,15,1006,15,13,104,1,99,9223372036854775807,0,-1];
+        let c = run(&code, Arithmetic::BigInt);
+        assert_eq!(c.output(), &[1]);
+        assert_eq!(c.peek(15), 0);
+        assert_eq!(c.peek_wide(15), BigInt::from(isize::MAX) * isize::MAX - 1);
+        assert!(run(&code, Arithmetic::Wrapping).output().is_empty());
+
+        let mut c = Computer::load(&[2,7,7,7,4,7,99,9223372036854775807]);
+        c.set_arithmetic(Arithmetic::BigInt);
+        assert_eq!(c.resume(), State::Fault(Fault { ip: 4, kind: FaultKind::Overflow }));
+        c = Computer::load(&[2,7,7,7,3,7,99,9223372036854775807]);
+        c.set_arithmetic(Arithmetic::BigInt);
+        c.push_input(5);
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.peek_wide(7), BigInt::from(5));
+    }
+
+    #[test]
+    fn parse() {
+        for a in [Arithmetic::Checked, Arithmetic::Wrapping, Arithmetic::BigInt].iter() {
+            assert_eq!(a.to_string().parse::<Arithmetic>().unwrap(), *a);
+        }
+        assert!("saturating".parse::<Arithmetic>().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use super::{Computer, Device, State};
+
+fn to_char(value: isize) -> Option<char> {
+    if (0..128).contains(&value) {
+        Some(value as u8 as char)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<isize>,
+}
+
+impl AsciiOutput {
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+
+    pub fn result(&self) -> Option<isize> {
+        self.values.last().copied()
+    }
+}
+
+pub struct Terminal<R, W> {
+    reader: R,
+    writer: W,
+    pending: VecDeque<isize>,
+    values: Vec<isize>,
+}
+
+impl<R: BufRead, W: Write> Terminal<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Terminal { reader, writer, pending: VecDeque::new(), values: Vec::new() }
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        self.pending.extend(line.chars().chain("\n".chars()).map(|c| c as isize));
+    }
+
+    pub fn values(&self) -> &[isize] {
+        &self.values
+    }
+
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<R: BufRead, W: Write> Device for Terminal<R, W> {
+    fn input(&mut self) -> Option<isize> {
+        if self.pending.is_empty() {
+            self.writer.flush().ok()?;
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            self.push_line(line.trim_end_matches(&['\r', '\n'][..]));
+        }
+        self.pending.pop_front()
+    }
+
+    fn output(&mut self, value: isize) -> Result<()> {
+        match to_char(value) {
+            Some(c) => write!(self.writer, "{}", c)?,
+            None => {
+                writeln!(self.writer, "{}", value)?;
+                self.values.push(value);
+            },
+        }
+        Ok(())
+    }
+}
+
+impl Computer {
+    pub fn push_str(&mut self, text: &str) {
+        self.input.extend(text.chars().map(|c| c as isize));
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        self.push_str(line);
+        self.push_input('\n' as isize);
+    }
+
+    pub fn take_ascii(&mut self) -> AsciiOutput {
+        let mut output = AsciiOutput::default();
+        for value in self.output.drain(..) {
+            match to_char(value) {
+                Some(c) => output.text.push(c),
+                None => output.values.push(value),
+            }
+        }
+        output
+    }
+
+    pub fn read_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        loop {
+            match self.run_until_event() {
+                State::Output(value) => match to_char(value) {
+                    Some('\n') => return Ok(Some(line)),
+                    Some(c) => line.push(c),
+                    None => self.output.push(value),
+                },
+                State::Halted | State::NeedsInput if line.is_empty() => return Ok(None),
+                State::Halted | State::NeedsInput => return Ok(Some(line)),
+                State::Fault(fault) => return Err(fault.into()),
+                State::BudgetExhausted => return Err(anyhow!("Budget exhausted")),
+                State::Stopped => return Err(anyhow!("Stopped")),
+            }
+        }
+    }
+
+    pub fn run_terminal<R: BufRead, W: Write>(&mut self, terminal: &mut Terminal<R, W>) -> Result<State> {
+        terminal.pending.extend(mem::take(&mut self.input));
+        let state = self.run_with(terminal)?;
+        terminal.writer.flush()?;
+        Ok(state)
+    }
+
+    pub fn interact(&mut self) -> Result<State> {
+        let stdin = io::stdin();
+        self.run_terminal(&mut Terminal::new(stdin.lock(), io::stdout()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ECHO_UPPER: [isize; 25] = [
+        3,24,1008,24,10,18,1005,18,19,1001,24,-32,24,4,24,1105,1,0,0,104,1000,1105,1,0,0,
+    ];
+
+    #[test]
+    fn lines_and_values() {
+        let mut c = Computer::load(&ECHO_UPPER);
+        c.push_line("abc");
+        c.push_str("de");
+        assert_eq!(c.resume(), State::NeedsInput);
+        let output = c.take_ascii();
+        assert_eq!(output.text, "ABCDE");
+        assert_eq!(output.lines().collect::<Vec<_>>(), &["ABCDE"]);
+        assert_eq!(output.values, &[1000]);
+        assert_eq!(output.result(), Some(1000));
+        assert!(c.output().is_empty());
+
+        let mut c = Computer::load(&[104,72,104,105,104,10,104,-1,104,33,99]);
+        assert_eq!(c.read_line().unwrap(), Some("Hi".to_owned()));
+        assert_eq!(c.read_line().unwrap(), Some("!".to_owned()));
+        assert_eq!(c.output(), &[-1]);
+        assert_eq!(c.read_line().unwrap(), None);
+    }
+
+    #[test]
+    fn terminal() {
+        let mut c = Computer::load(&ECHO_UPPER);
+        c.push_line("go");
+        let mut t = Terminal::new("north\r\nsouth".as_bytes(), Vec::new());
+        assert_eq!(c.run_terminal(&mut t).unwrap(), State::NeedsInput);
+        assert_eq!(t.values(), &[1000, 1000, 1000]);
+        assert_eq!(String::from_utf8(t.into_writer()).unwrap(), "GO1000\nNORTH1000\nSOUTH1000\n");
+    }
+}
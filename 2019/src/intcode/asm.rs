@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::Opcode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for AsmError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(isize),
+    Label(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+#[derive(Debug, Clone)]
+struct Operand {
+    mode: Mode,
+    value: Value,
+    line: usize,
+    column: usize,
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str, line: usize) -> Self {
+        Cursor { text, pos: 0, line }
+    }
+
+    fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, AsmError> {
+        Err(AsmError { line: self.line, column: self.column(), message })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.pos += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.')).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn number(&mut self) -> Result<isize, AsmError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let sign = if rest.starts_with('-') || rest.starts_with('+') { 1 } else { 0 };
+        let len = rest[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len() - sign);
+        match rest[..sign + len].parse::<isize>() {
+            Ok(n) => {
+                self.pos += sign + len;
+                Ok(n)
+            },
+            Err(_) => {
+                self.pos = start;
+                self.error(format!("Invalid number `{}`", &rest[..sign + len]))
+            },
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, AsmError> {
+        match self.rest().chars().next() {
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => Ok(Value::Number(self.number()?)),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => Ok(Value::Label(self.word().to_owned())),
+            Some(c) => self.error(format!("Unexpected character `{}`", c)),
+            None => self.error("Missing value".to_owned()),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, AsmError> {
+        self.skip_whitespace();
+        let (line, column) = (self.line, self.column());
+        let (mode, value) = if self.eat("#") {
+            (Mode::Immediate, self.value()?)
+        } else if self.rest().starts_with("rel+") || self.rest().starts_with("rel-") {
+            self.pos += 3;
+            (Mode::Relative, Value::Number(self.number()?))
+        } else {
+            (Mode::Position, self.value()?)
+        };
+        Ok(Operand { mode, value, line, column })
+    }
+
+    fn operands(&mut self) -> Result<Vec<Operand>, AsmError> {
+        let mut operands = Vec::<Operand>::new();
+        if self.at_end() {
+            return Ok(operands);
+        }
+        loop {
+            operands.push(self.operand()?);
+            if self.at_end() {
+                return Ok(operands);
+            }
+            if !self.eat(",") {
+                return self.error("Expected `,`".to_owned());
+            }
+        }
+    }
+}
+
+fn parse_line(cursor: &mut Cursor, addr: usize, labels: &mut HashMap<String, usize>, code: &mut Vec<(isize, Option<Operand>)>) -> Result<(), AsmError> {
+    loop {
+        cursor.skip_whitespace();
+        let start = cursor.pos;
+        let column = cursor.column();
+        let word = cursor.word();
+        if word.is_empty() || !cursor.eat(":") {
+            cursor.pos = start;
+            break;
+        }
+        if word.chars().all(|c| c.is_ascii_digit()) {
+            if word.parse::<usize>().ok() != Some(addr) {
+                return Err(AsmError { line: cursor.line, column, message: format!("Address {} does not match actual address {}", word, addr) });
+            }
+        } else if word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            if labels.insert(word.to_owned(), addr).is_some() {
+                return Err(AsmError { line: cursor.line, column, message: format!("Duplicate label `{}`", word) });
+            }
+        } else {
+            return Err(AsmError { line: cursor.line, column, message: format!("Invalid label `{}`", word) });
+        }
+    }
+
+    if cursor.at_end() {
+        return Ok(());
+    }
+    let column = cursor.column();
+    let mnemonic = cursor.word();
+    let operands = cursor.operands()?;
+
+    if mnemonic == ".data" {
+        if operands.is_empty() {
+            return Err(AsmError { line: cursor.line, column, message: "Missing data".to_owned() });
+        }
+        for operand in operands {
+            if operand.mode != Mode::Position {
+                return Err(AsmError { line: operand.line, column: operand.column, message: "Data must not have addressing mode".to_owned() });
+            }
+            code.push((0, Some(operand)));
+        }
+        return Ok(());
+    }
+
+    let op = match Opcode::from_str(mnemonic) {
+        Ok(op) => op,
+        Err(_) => return Err(AsmError { line: cursor.line, column, message: format!("Unknown mnemonic `{}`", mnemonic) }),
+    };
+    if operands.len() != op.params() {
+        return Err(AsmError { line: cursor.line, column, message: format!("`{}` takes {} operands, found {}", op, op.params(), operands.len()) });
+    }
+    let mut instruction = op as isize;
+    for (idx, operand) in operands.iter().enumerate() {
+        let mode = match operand.mode {
+            Mode::Position => 0,
+            Mode::Immediate if op.output_param() == Some(idx as u32 + 1) => {
+                return Err(AsmError { line: operand.line, column: operand.column, message: "Output operand must not be immediate".to_owned() });
+            },
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        };
+        instruction += mode * 10isize.pow(idx as u32 + 2);
+    }
+    code.push((instruction, None));
+    code.extend(operands.into_iter().map(|operand| (0, Some(operand))));
+    Ok(())
+}
+
+pub fn assemble(source: &str) -> Result<Vec<isize>, AsmError> {
+    let mut labels = HashMap::<String, usize>::new();
+    let mut code = Vec::<(isize, Option<Operand>)>::new();
+
+    for (idx, line) in source.lines().enumerate() {
+        let text = line.split(';').next().unwrap();
+        parse_line(&mut Cursor::new(text, idx + 1), code.len(), &mut labels, &mut code)?;
+    }
+
+    code.into_iter().map(|(word, operand)| match operand {
+        None => Ok(word),
+        Some(Operand { value: Value::Number(n), .. }) => Ok(n),
+        Some(Operand { value: Value::Label(label), line, column, .. }) => labels.get(&label)
+            .map(|addr| *addr as isize)
+            .ok_or(AsmError { line, column, message: format!("Undefined label `{}`", label) }),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use super::*;
+    use super::super::Computer;
+
+    #[test]
+    fn instructions() {
+        assert_eq!(assemble("mul 4, #3, 4\n.data 33").unwrap(), &[1002,4,3,4,33]);
+        assert_eq!(assemble("in rel+1985\narb #19\nout rel-34\nhlt").unwrap(), &[203,1985,109,19,204,-34,99]);
+        assert_eq!(assemble("  ; nothing here\n\n").unwrap(), &[]);
+    }
+
+    #[test]
+    fn labels() {
+        let code = assemble(indoc!("
+            ; compare input with 8
+            start:  in    value
+                    eq    value, #8, value
+                    out   value        ; 1 if equal
+                    hlt
+            value:  .data -1
+                    .data 8
+        ")).unwrap();
+        assert_eq!(code, &[3,9,1008,9,8,9,4,9,99,-1,8]);
+
+        let mut c = Computer::load(&code);
+        c.push_input(8);
+        assert!(c.run().is_ok());
+        assert_eq!(c.pop_output().unwrap(), 1);
+
+        assert_eq!(assemble("loop: jt #1, #loop").unwrap(), &[1105,1,0]);
+        assert_eq!(assemble("jf #0, #end\n.data 1, -2, end\nend: hlt").unwrap(), &[1106,0,6,1,-2,6,99]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(assemble("hlt\n  foo 1, 2").unwrap_err(), AsmError { line: 2, column: 3, message: "Unknown mnemonic `foo`".to_owned() });
+        assert_eq!(assemble("add 1, 2").unwrap_err().to_string(), "1:1: `add` takes 3 operands, found 2");
+        assert_eq!(assemble("add 1, 2, #3").unwrap_err().column, 11);
+        assert_eq!(assemble("out x1\nhlt").unwrap_err(), AsmError { line: 1, column: 5, message: "Undefined label `x1`".to_owned() });
+        assert_eq!(assemble("a: hlt\n a: hlt").unwrap_err().column, 2);
+        assert_eq!(assemble("out 1 2").unwrap_err().column, 7);
+        assert_eq!(assemble("out --2").unwrap_err().column, 5);
+        assert_eq!(assemble("out #%").unwrap_err().column, 6);
+        assert_eq!(assemble(".data #2").unwrap_err().column, 7);
+        assert_eq!(assemble("hlt\n3: hlt").unwrap_err().message, "Address 3 does not match actual address 1");
+    }
+
+    #[test]
+    fn disassembler_round_trip() {
+        let programs: &[&[isize]] = &[
+            &[3,9,8,9,10,9,4,9,99,-1,8],
+            &[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9],
+            &[109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99],
+            &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99],
+        ];
+        for program in programs {
+            let listing = Computer::load(program).disassemble().to_string();
+            assert_eq!(&assemble(&listing).unwrap(), program);
+        }
+    }
+}
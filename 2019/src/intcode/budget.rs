@@ -0,0 +1,172 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use super::{Computer, State};
+
+const CLOCK_INTERVAL: u32 = 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    steps: Option<u64>,
+    deadline: Option<Instant>,
+    stop: Option<Arc<AtomicBool>>,
+    ticks: u32,
+}
+
+impl Budget {
+    pub fn steps(steps: u64) -> Self {
+        Budget { steps: Some(steps), ..Budget::default() }
+    }
+
+    pub fn timeout(timeout: Duration) -> Self {
+        Budget::deadline(Instant::now() + timeout)
+    }
+
+    pub fn deadline(deadline: Instant) -> Self {
+        Budget { deadline: Some(deadline), ..Budget::default() }
+    }
+
+    pub fn with_steps(self, steps: u64) -> Self {
+        Budget { steps: Some(steps), ..self }
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Budget { deadline: Some(Instant::now() + timeout), ..self }
+    }
+
+    pub fn with_stop(self, stop: Arc<AtomicBool>) -> Self {
+        Budget { stop: Some(stop), ..self }
+    }
+
+    pub fn remaining_steps(&self) -> Option<u64> {
+        self.steps
+    }
+
+    fn interrupt(&mut self) -> Option<State> {
+        if self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+            return Some(State::Stopped);
+        }
+        let exhausted = self.steps == Some(0) || match self.deadline {
+            Some(deadline) if self.ticks == 0 => {
+                self.ticks = CLOCK_INTERVAL;
+                Instant::now() >= deadline
+            },
+            _ => false,
+        };
+        if exhausted { Some(State::BudgetExhausted) } else { None }
+    }
+
+    fn charge(&mut self) {
+        if let Some(steps) = self.steps.as_mut() {
+            *steps -= 1;
+        }
+        self.ticks = self.ticks.saturating_sub(1);
+    }
+}
+
+impl Computer {
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = Some(budget);
+    }
+
+    pub fn clear_budget(&mut self) -> Option<Budget> {
+        self.budget.take()
+    }
+
+    pub fn budget(&self) -> Option<&Budget> {
+        self.budget.as_ref()
+    }
+
+    pub(super) fn set_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.budget = Some(self.budget.take().unwrap_or_default().with_stop(stop));
+    }
+
+    pub(super) fn clear_stop(&mut self) {
+        if let Some(budget) = self.budget.as_mut() {
+            budget.stop = None;
+            if budget.steps.is_none() && budget.deadline.is_none() {
+                self.budget = None;
+            }
+        }
+    }
+
+    #[inline]
+    pub(super) fn budget_interrupt(&mut self) -> Option<State> {
+        self.budget.as_mut()?.interrupt()
+    }
+
+    #[inline]
+    pub(super) fn charge_budget(&mut self) {
+        if let Some(budget) = self.budget.as_mut() {
+            budget.charge();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const COUNTER: [isize; 8] = [1001,7,1,7,1105,1,0,0];
+
+    #[test]
+    fn step_budget() {
+        let mut c = Computer::load(&[1101,2,3,7,4,7,99,0]);
+        c.set_budget(Budget::steps(1));
+        assert_eq!(c.resume(), State::BudgetExhausted);
+        assert_eq!((c.ip(), c.peek(7)), (4, 5));
+        assert_eq!(c.run().unwrap_err().to_string(), "Budget exhausted");
+
+        c.set_budget(Budget::steps(10));
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.output(), &[5]);
+        assert_eq!(c.budget().unwrap().remaining_steps(), Some(9));
+
+        let mut c = Computer::load(&COUNTER);
+        c.set_budget(Budget::steps(1000));
+        assert_eq!(c.run_until_event(), State::BudgetExhausted);
+        assert_eq!(c.peek(7), 500);
+        c.compile();
+        c.set_budget(Budget::steps(1000));
+        assert_eq!(c.resume(), State::BudgetExhausted);
+        assert_eq!(c.peek(7), 1000);
+        assert!(c.clear_budget().is_some());
+    }
+
+    #[test]
+    fn deadline() {
+        let mut c = Computer::load(&COUNTER);
+        c.set_budget(Budget::timeout(Duration::from_millis(20)));
+        assert_eq!(c.resume(), State::BudgetExhausted);
+        assert!(c.peek(7) > 0);
+
+        c.set_budget(Budget::deadline(Instant::now()).with_steps(5));
+        assert_eq!(c.run_single_step().unwrap_err().to_string(), "Budget exhausted");
+        c.set_budget(Budget::steps(5).with_timeout(Duration::from_secs(60)));
+        assert!(c.run_until_output().is_err());
+        assert_eq!(c.budget().unwrap().remaining_steps(), Some(0));
+    }
+
+    #[test]
+    fn stop_flag() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut c = Computer::load(&COUNTER);
+        c.set_budget(Budget::steps(10).with_stop(stop.clone()));
+        assert_eq!(c.run_until_event(), State::BudgetExhausted);
+        assert_eq!(c.peek(7), 5);
+
+        stop.store(true, Ordering::Relaxed);
+        c.clear_budget();
+        c.set_stop(stop.clone());
+        assert_eq!(c.resume(), State::Stopped);
+        assert_eq!(c.run().unwrap_err().to_string(), "Stopped");
+        assert_eq!(c.peek(7), 5);
+        c.clear_stop();
+        assert!(c.budget().is_none());
+
+        c.set_budget(Budget::steps(0).with_stop(stop));
+        assert_eq!(c.run_until_event(), State::Stopped);
+        c.clear_stop();
+        assert_eq!(c.run_until_event(), State::BudgetExhausted);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use super::{Computer, Item, Line, Opcode, Param};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exit {
+    Next(usize),
+    Jump(usize),
+    Branch { taken: usize, next: usize },
+    Call { target: usize, ret: usize },
+    Return,
+    Indirect(Option<usize>),
+    Halt,
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block<'a> {
+    pub start: usize,
+    pub lines: Vec<Line<'a>>,
+    pub exit: Exit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub entry: usize,
+    pub blocks: Vec<usize>,
+    pub calls: Vec<usize>,
+    pub frame: Option<isize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlFlow<'a> {
+    pub blocks: BTreeMap<usize, Block<'a>>,
+    pub functions: Vec<Function>,
+}
+
+impl Exit {
+    pub fn successors(&self) -> Vec<usize> {
+        match *self {
+            Exit::Next(addr) | Exit::Jump(addr) => vec![addr],
+            Exit::Branch { taken, next } => vec![taken, next],
+            Exit::Call { ret, .. } => vec![ret],
+            Exit::Indirect(next) => next.into_iter().collect(),
+            Exit::Return | Exit::Halt | Exit::Invalid => Vec::new(),
+        }
+    }
+
+    fn targets(&self) -> Vec<usize> {
+        match *self {
+            Exit::Call { target, ret } => vec![target, ret],
+            _ => self.successors(),
+        }
+    }
+}
+
+fn stores(item: Option<&Item>, value: usize) -> bool {
+    match item {
+        Some(Item::Code(Opcode::Add, params)) => matches!(params[..2], [Param::Immediate(a), Param::Immediate(b)] if a + b == value as isize),
+        Some(Item::Code(Opcode::Mul, params)) => matches!(params[..2], [Param::Immediate(a), Param::Immediate(b)] if a * b == value as isize),
+        _ => false,
+    }
+}
+
+fn exit(addr: usize, item: &Item, prev: Option<&Item>) -> Option<Exit> {
+    let (op, params) = match item {
+        Item::Code(op, params) => (*op, params),
+        Item::Data(_) => return Some(Exit::Invalid),
+    };
+    let next = addr + item.size();
+    let always = match (op, params.first()) {
+        (Opcode::Hlt, _) => return Some(Exit::Halt),
+        (Opcode::Jt, Some(Param::Immediate(c))) if **c == 0 => return None,
+        (Opcode::Jf, Some(Param::Immediate(c))) if **c != 0 => return None,
+        (Opcode::Jt, Some(cond)) | (Opcode::Jf, Some(cond)) => matches!(cond, Param::Immediate(_)),
+        _ => return None,
+    };
+    Some(match (always, params[1]) {
+        (true, Param::Immediate(t)) if *t >= 0 && stores(prev, next) => Exit::Call { target: *t as usize, ret: next },
+        (true, Param::Immediate(t)) if *t >= 0 => Exit::Jump(*t as usize),
+        (true, Param::Relative(_)) => Exit::Return,
+        (true, _) => Exit::Indirect(None),
+        (false, Param::Immediate(t)) if *t >= 0 => Exit::Branch { taken: *t as usize, next },
+        (false, _) => Exit::Indirect(Some(next)),
+    })
+}
+
+impl ControlFlow<'_> {
+    pub fn function(&self, entry: usize) -> Option<&Function> {
+        self.functions.iter().find(|f| f.entry == entry)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let mut placed = BTreeSet::<usize>::new();
+        writeln!(dot, "digraph intcode {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+        for function in &self.functions {
+            writeln!(dot, "    subgraph cluster_{} {{", function.entry).unwrap();
+            writeln!(dot, "        label=\"fn_{}\";", function.entry).unwrap();
+            for start in function.blocks.iter().filter(|b| placed.insert(**b)) {
+                let label = self.blocks[start].lines.iter()
+                    .map(|line| format!("{}: {}\\l", line.addr, line.item))
+                    .collect::<String>();
+                writeln!(dot, "        b{} [label=\"{}\"];", start, label).unwrap();
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+        for block in self.blocks.values() {
+            for succ in block.exit.successors() {
+                writeln!(dot, "    b{} -> b{};", block.start, succ).unwrap();
+            }
+            if let Exit::Call { target, .. } = block.exit {
+                writeln!(dot, "    b{} -> b{} [style=dashed];", block.start, target).unwrap();
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+impl Computer {
+    pub fn control_flow(&self) -> ControlFlow<'_> {
+        let mut code = BTreeMap::<usize, Item>::new();
+        let mut exits = BTreeMap::<usize, Exit>::new();
+        let mut leaders = BTreeSet::<usize>::new();
+        let mut pending = vec![0usize];
+        leaders.insert(0);
+
+        while let Some(start) = pending.pop() {
+            let mut addr = start;
+            let mut prev = None;
+            while !code.contains_key(&addr) {
+                let item = self.decode_at(addr).unwrap_or_else(|_| Item::Data(self.peek(addr)));
+                let exit = exit(addr, &item, prev.as_ref());
+                let next = addr + item.size();
+                code.insert(addr, item.clone());
+                match exit {
+                    Some(exit) => {
+                        for target in exit.targets() {
+                            if leaders.insert(target) {
+                                pending.push(target);
+                            }
+                        }
+                        exits.insert(addr, exit);
+                        break;
+                    },
+                    None => {
+                        prev = Some(item);
+                        addr = next;
+                    },
+                }
+            }
+            if addr != start && !exits.contains_key(&addr) {
+                leaders.insert(addr);
+            }
+        }
+
+        let mut blocks = BTreeMap::<usize, Block>::new();
+        for start in leaders.iter().copied() {
+            let mut lines = Vec::<Line>::new();
+            let mut addr = start;
+            let exit = loop {
+                let item = code[&addr].clone();
+                let next = addr + item.size();
+                lines.push(Line { addr, item });
+                if let Some(exit) = exits.get(&addr) {
+                    break *exit;
+                }
+                if leaders.contains(&next) {
+                    break Exit::Next(next);
+                }
+                addr = next;
+            };
+            if exit == Exit::Invalid {
+                lines.pop();
+            }
+            blocks.insert(start, Block { start, lines, exit });
+        }
+
+        let mut entries = vec![0usize];
+        entries.extend(blocks.values().filter_map(|b| match b.exit {
+            Exit::Call { target, .. } => Some(target),
+            _ => None,
+        }));
+        entries.sort_unstable();
+        entries.dedup();
+
+        let functions = entries.into_iter().map(|entry| {
+            let mut reached = BTreeSet::<usize>::new();
+            let mut pending = vec![entry];
+            let mut calls = Vec::<usize>::new();
+            while let Some(start) = pending.pop() {
+                if reached.insert(start) {
+                    let block = &blocks[&start];
+                    if let Exit::Call { target, .. } = block.exit {
+                        calls.push(target);
+                    }
+                    pending.extend(block.exit.successors());
+                }
+            }
+            calls.sort_unstable();
+            calls.dedup();
+            let frame = match blocks[&entry].lines.first().map(|l| &l.item) {
+                Some(Item::Code(Opcode::Arb, params)) => match params[0] {
+                    Param::Immediate(n) if *n > 0 => Some(*n),
+                    _ => None,
+                },
+                _ => None,
+            };
+            Function { entry, blocks: reached.into_iter().collect(), calls, frame }
+        }).collect();
+
+        ControlFlow { blocks, functions }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CALLS: [isize; 29] = [
+        1105,1,14,
+        109,2, 21101,0,0,-1, 109,-2, 2106,0,0,
+        21101,21,0,0, 1105,1,3,
+        21101,28,0,0, 1105,1,3, 99,
+    ];
+
+    fn starts(flow: &ControlFlow) -> Vec<(usize, Vec<usize>, Exit)> {
+        flow.blocks.values().map(|b| (b.start, b.lines.iter().map(|l| l.addr).collect(), b.exit)).collect()
+    }
+
+    #[test]
+    fn basic_blocks() {
+        let c = Computer::load(&[3,9,1005,9,7,104,0,99,0,0]);
+        let flow = c.control_flow();
+        assert_eq!(starts(&flow), &[
+            (0, vec![0, 2], Exit::Branch { taken: 7, next: 5 }),
+            (5, vec![5], Exit::Next(7)),
+            (7, vec![7], Exit::Halt),
+        ]);
+        assert_eq!(flow.functions, &[Function { entry: 0, blocks: vec![0, 5, 7], calls: vec![], frame: None }]);
+
+        let c = Computer::load(&[1106,0,5,99,99,5,0,1,1,1,1,1]);
+        assert_eq!(starts(&c.control_flow()), &[
+            (0, vec![0], Exit::Jump(5)),
+            (5, vec![5], Exit::Indirect(Some(8))),
+            (8, vec![8], Exit::Invalid),
+        ]);
+    }
+
+    #[test]
+    fn calls_and_returns() {
+        let c = Computer::load(&CALLS);
+        let flow = c.control_flow();
+        assert_eq!(starts(&flow), &[
+            (0, vec![0], Exit::Jump(14)),
+            (3, vec![3, 5, 9, 11], Exit::Return),
+            (14, vec![14, 18], Exit::Call { target: 3, ret: 21 }),
+            (21, vec![21, 25], Exit::Call { target: 3, ret: 28 }),
+            (28, vec![28], Exit::Halt),
+        ]);
+        assert_eq!(flow.functions, &[
+            Function { entry: 0, blocks: vec![0, 14, 21, 28], calls: vec![3], frame: None },
+            Function { entry: 3, blocks: vec![3], calls: vec![], frame: Some(2) },
+        ]);
+        assert_eq!(flow.function(3).unwrap().frame, Some(2));
+    }
+
+    #[test]
+    fn dot() {
+        assert_eq!(Computer::load(&CALLS).control_flow().to_dot(), concat!(
+            "digraph intcode {\n",
+            "    node [shape=box, fontname=\"monospace\"];\n",
+            "    subgraph cluster_0 {\n",
+            "        label=\"fn_0\";\n",
+            "        b0 [label=\"0: jt    #1, #14\\l\"];\n",
+            "        b14 [label=\"14: add   #21, #0, rel+0\\l18: jt    #1, #3\\l\"];\n",
+            "        b21 [label=\"21: add   #28, #0, rel+0\\l25: jt    #1, #3\\l\"];\n",
+            "        b28 [label=\"28: hlt\\l\"];\n",
+            "    }\n",
+            "    subgraph cluster_3 {\n",
+            "        label=\"fn_3\";\n",
+            "        b3 [label=\"3: arb   #2\\l5: add   #0, #0, rel-1\\l9: arb   #-2\\l11: jf    #0, rel+0\\l\"];\n",
+            "    }\n",
+            "    b0 -> b14;\n",
+            "    b14 -> b21;\n",
+            "    b14 -> b3 [style=dashed];\n",
+            "    b21 -> b28;\n",
+            "    b21 -> b3 [style=dashed];\n",
+            "}\n",
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{anyhow, ensure, Result};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use super::{Computer, Device, State};
+
+enum Message {
+    Value(isize),
+    Shutdown,
+}
+
+enum Event {
+    Output(isize),
+    Waiting(usize),
+    Done(State),
+    Failed,
+}
+
+struct Port {
+    id: usize,
+    input: Receiver<Message>,
+    events: Sender<(usize, Event)>,
+    consumed: usize,
+}
+
+impl Device for Port {
+    fn input(&mut self) -> Option<isize> {
+        let message = match self.input.try_recv() {
+            Ok(message) => message,
+            Err(_) => {
+                self.events.send((self.id, Event::Waiting(self.consumed))).ok()?;
+                self.input.recv().ok()?
+            },
+        };
+        match message {
+            Message::Value(value) => {
+                self.consumed += 1;
+                Some(value)
+            },
+            Message::Shutdown => None,
+        }
+    }
+
+    fn output(&mut self, value: isize) -> Result<()> {
+        self.events.send((self.id, Event::Output(value)))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub computer: Computer,
+    pub state: State,
+    pub output: Vec<isize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Circuit {
+    computers: Vec<Computer>,
+    links: Vec<Vec<usize>>,
+    draining: bool,
+}
+
+impl Circuit {
+    pub fn new() -> Self {
+        Circuit::default()
+    }
+
+    pub fn ring(computers: Vec<Computer>) -> Self {
+        let mut circuit = Circuit::new();
+        let size = computers.len();
+        for computer in computers {
+            circuit.add(computer);
+        }
+        for id in 0..size {
+            circuit.connect(id, (id + 1) % size).unwrap();
+        }
+        circuit
+    }
+
+    pub fn draining(self) -> Self {
+        Circuit { draining: true, ..self }
+    }
+
+    pub fn add(&mut self, computer: Computer) -> usize {
+        self.computers.push(computer);
+        self.links.push(Vec::new());
+        self.computers.len() - 1
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) -> Result<()> {
+        ensure!(from < self.computers.len() && to < self.computers.len(), "No machine {}", from.max(to));
+        self.links[from].push(to);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.computers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.computers.is_empty()
+    }
+
+    pub fn run(self) -> Result<Vec<Node>> {
+        let Circuit { computers, links, draining } = self;
+        let size = computers.len();
+        let (events, router) = channel::<(usize, Event)>();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        thread::scope(|scope| {
+            let mut inputs = Vec::with_capacity(size);
+            let mut delivered = vec![0; size];
+            let mut handles = Vec::with_capacity(size);
+            for (id, mut computer) in computers.into_iter().enumerate() {
+                let (sender, receiver) = channel();
+                for value in mem::take(&mut computer.input) {
+                    sender.send(Message::Value(value))?;
+                    delivered[id] += 1;
+                }
+                inputs.push(sender);
+                let mut port = Port { id, input: receiver, events: events.clone(), consumed: 0 };
+                computer.set_stop(stop.clone());
+                handles.push(scope.spawn(move || -> Result<Computer> {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| computer.run_with(&mut port)));
+                    computer.clear_stop();
+                    match result {
+                        Ok(Ok(state)) => {
+                            port.events.send((id, Event::Done(state))).ok();
+                            Ok(computer)
+                        },
+                        Ok(Err(e)) => {
+                            port.events.send((id, Event::Failed)).ok();
+                            Err(e)
+                        },
+                        Err(payload) => {
+                            port.events.send((id, Event::Failed)).ok();
+                            panic::resume_unwind(payload)
+                        },
+                    }
+                }));
+            }
+            drop(events);
+
+            let mut output = vec![Vec::new(); size];
+            let mut waiting = vec![None; size];
+            let mut done = vec![None; size];
+            let mut failed = vec![false; size];
+            let mut stopping = false;
+            while (0..size).any(|i| done[i].is_none() && !failed[i]) {
+                let (id, event) = router.recv().map_err(|_| anyhow!("Machine stopped without a result"))?;
+                match event {
+                    Event::Output(value) => {
+                        output[id].push(value);
+                        for &to in &links[id] {
+                            if done[to].is_none() && inputs[to].send(Message::Value(value)).is_ok() {
+                                delivered[to] += 1;
+                            }
+                        }
+                    },
+                    Event::Waiting(consumed) => waiting[id] = Some(consumed),
+                    Event::Done(state) => {
+                        done[id] = Some(state);
+                        if !draining {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                    },
+                    Event::Failed => {
+                        failed[id] = true;
+                        stop.store(true, Ordering::Relaxed);
+                    },
+                }
+                let starved = (0..size).all(|i| done[i].is_some() || failed[i] || waiting[i] == Some(delivered[i]));
+                if (starved || stop.load(Ordering::Relaxed)) && !stopping {
+                    stopping = true;
+                    for (_, input) in inputs.iter().enumerate().filter(|(i, _)| done[*i].is_none() && !failed[*i]) {
+                        input.send(Message::Shutdown).ok();
+                    }
+                }
+            }
+
+            let results = handles.into_iter().map(|handle| handle.join()).collect::<Vec<_>>();
+            results.into_iter().zip(done).zip(output)
+                .map(|((result, state), output)| {
+                    let computer = result.map_err(|_| anyhow!("Machine panicked"))??;
+                    Ok(Node { computer, state: state.ok_or_else(|| anyhow!("Machine stopped without a result"))?, output })
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::Budget;
+
+    const FEEDBACK: [isize; 29] = [3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5];
+
+    fn amplifiers(phases: &[isize]) -> Circuit {
+        let computers = phases.iter().map(|phase| {
+            let mut c = Computer::load(&FEEDBACK);
+            c.push_input(*phase);
+            c
+        }).collect::<Vec<_>>();
+        let mut circuit = Circuit::ring(computers).draining();
+        circuit.computers[0].push_input(0);
+        circuit
+    }
+
+    #[test]
+    fn feedback_ring() {
+        for _ in 0..20 {
+            let nodes = amplifiers(&[9,8,7,6,5]).run().unwrap();
+            assert!(nodes.iter().all(|n| n.state == State::Halted));
+            assert_eq!(nodes[4].output.last(), Some(&139629729));
+            assert_eq!(nodes[0].output.len(), 5);
+        }
+    }
+
+    #[test]
+    fn starved_shutdown() {
+        let echo = Computer::load(&[3,7,4,7,1105,1,0,0]);
+        let circuit = Circuit::ring(vec![echo.clone(), echo.clone(), echo]);
+        assert_eq!(circuit.len(), 3);
+        let nodes = circuit.run().unwrap();
+        assert!(nodes.iter().all(|n| n.state == State::NeedsInput));
+        assert!(nodes.iter().all(|n| n.computer.ip() == 0));
+
+        let mut circuit = Circuit::new();
+        let source = circuit.add(Computer::load(&[104,1,104,2,99]));
+        let sink = circuit.add(Computer::load(&[3,9,1001,9,10,9,4,9,99,0]));
+        assert!(circuit.connect(source, 2).is_err());
+        circuit.connect(source, sink).unwrap();
+        let nodes = circuit.draining().run().unwrap();
+        assert_eq!((nodes[0].state, nodes[1].state), (State::Halted, State::Halted));
+        assert_eq!(nodes[1].output, &[11]);
+        assert_eq!(nodes[1].computer.input().len(), 0);
+    }
+
+    #[test]
+    fn budgets() {
+        let mut circuit = Circuit::new();
+        circuit.add(Computer::load(&[3,5,4,5,99,0]));
+        let mut counter = Computer::load(&[1001,7,1,7,1105,1,0,0]);
+        counter.set_budget(Budget::steps(100));
+        circuit.add(counter);
+        circuit.connect(1, 0).unwrap();
+        let nodes = circuit.run().unwrap();
+        assert_eq!(nodes[0].state, State::NeedsInput);
+        assert_eq!(nodes[1].state, State::BudgetExhausted);
+        assert_eq!(nodes[1].computer.peek(7), 50);
+    }
+
+    #[test]
+    fn halt_stops_circuit() {
+        let mut circuit = Circuit::new();
+        let source = circuit.add(Computer::load(&[104,1,99]));
+        let spinner = circuit.add(Computer::load(&[1001,7,1,7,1105,1,0,0]));
+        circuit.connect(source, spinner).unwrap();
+        let nodes = circuit.run().unwrap();
+        assert_eq!(nodes[0].state, State::Halted);
+        assert_eq!(nodes[1].state, State::Stopped);
+        assert!(nodes[1].computer.budget().is_none());
+
+        let mut waiting = Computer::load(&[3,5,4,5,99,0]);
+        let mut bounded = Computer::load(&[1001,7,1,7,1105,1,0,0]);
+        bounded.set_budget(Budget::steps(1_000_000_000));
+        waiting.push_input(3);
+        let mut circuit = Circuit::new();
+        circuit.add(waiting);
+        circuit.add(bounded);
+        let nodes = circuit.run().unwrap();
+        assert_eq!((nodes[0].state, nodes[0].output.as_slice()), (State::Halted, &[3][..]));
+        assert_eq!(nodes[1].state, State::Stopped);
+        assert!(nodes[1].computer.budget().unwrap().remaining_steps().unwrap() > 0);
+    }
+}
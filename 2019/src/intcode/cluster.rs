@@ -0,0 +1,265 @@
+use anyhow::{anyhow, ensure, Result};
+use std::collections::BTreeMap;
+use super::{Computer, State};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packet {
+    pub dest: usize,
+    pub x: isize,
+    pub y: isize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    RoundRobin,
+    Demand,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Round {
+    pub sent: Vec<Packet>,
+    pub idle: bool,
+    pub nat: Option<Packet>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    computer: Computer,
+    partial: Vec<isize>,
+    halted: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Nat {
+    addr: usize,
+    target: usize,
+    last: Option<Packet>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    nodes: BTreeMap<usize, Node>,
+    nat: Option<Nat>,
+    schedule: Schedule,
+    undelivered: Vec<Packet>,
+}
+
+impl Cluster {
+    pub fn new(code: &[isize], size: usize) -> Self {
+        let mut cluster = Cluster {
+            nodes: BTreeMap::new(),
+            nat: None,
+            schedule: Schedule::RoundRobin,
+            undelivered: Vec::new(),
+        };
+        for addr in 0..size {
+            let mut computer = Computer::load(code);
+            computer.push_input(addr as isize);
+            cluster.attach(addr, computer);
+        }
+        cluster
+    }
+
+    pub fn attach(&mut self, addr: usize, computer: Computer) {
+        self.nodes.insert(addr, Node { computer, partial: Vec::new(), halted: false });
+    }
+
+    pub fn set_nat(&mut self, addr: usize, target: usize) {
+        self.nat = Some(Nat { addr, target, last: None });
+    }
+
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    pub fn computer(&self, addr: usize) -> Option<&Computer> {
+        self.nodes.get(&addr).map(|n| &n.computer)
+    }
+
+    pub fn nat_packet(&self) -> Option<Packet> {
+        self.nat.as_ref().and_then(|n| n.last)
+    }
+
+    pub fn undelivered(&self) -> &[Packet] {
+        &self.undelivered
+    }
+
+    pub fn send(&mut self, packet: Packet) {
+        if let Some(nat) = self.nat.as_mut().filter(|n| n.addr == packet.dest) {
+            nat.last = Some(packet);
+        } else if let Some(node) = self.nodes.get_mut(&packet.dest).filter(|n| !n.halted) {
+            node.computer.push_input(packet.x);
+            node.computer.push_input(packet.y);
+        } else {
+            self.undelivered.push(packet);
+        }
+    }
+
+    fn run_node(&mut self, addr: usize) -> Result<Vec<Packet>> {
+        let node = self.nodes.get_mut(&addr).ok_or_else(|| anyhow!("No node at {}", addr))?;
+        let mut packets = Vec::<Packet>::new();
+        if node.halted {
+            return Ok(packets);
+        }
+        if node.computer.input().is_empty() {
+            node.computer.push_input(-1);
+        }
+        loop {
+            match node.computer.run_until_event() {
+                State::Output(value) => {
+                    node.partial.push(value);
+                    if let [dest, x, y] = node.partial[..] {
+                        ensure!(dest >= 0, "Invalid destination {} from node {}", dest, addr);
+                        packets.push(Packet { dest: dest as usize, x, y });
+                        node.partial.clear();
+                    }
+                },
+                State::NeedsInput | State::BudgetExhausted | State::Stopped => return Ok(packets),
+                State::Halted => {
+                    node.halted = true;
+                    return Ok(packets);
+                },
+                State::Fault(fault) => return Err(anyhow!("Node {}: {}", addr, fault)),
+            }
+        }
+    }
+
+    fn pending(&self) -> Vec<usize> {
+        self.nodes.iter()
+            .filter(|(_, n)| !n.halted && !n.computer.input().is_empty())
+            .map(|(a, _)| *a)
+            .collect()
+    }
+
+    pub fn round(&mut self) -> Result<Round> {
+        let pending = self.pending();
+        let order = match self.schedule {
+            Schedule::Demand if !pending.is_empty() => pending.clone(),
+            _ => self.nodes.keys().copied().collect(),
+        };
+
+        let mut round = Round::default();
+        for addr in order {
+            for packet in self.run_node(addr)? {
+                self.send(packet);
+                round.sent.push(packet);
+            }
+        }
+
+        round.idle = pending.is_empty() && round.sent.is_empty();
+        if round.idle {
+            if let Some(nat) = self.nat.as_mut() {
+                if let Some(packet) = nat.last {
+                    let packet = Packet { dest: nat.target, ..packet };
+                    self.send(packet);
+                    round.nat = Some(packet);
+                }
+            }
+        }
+        Ok(round)
+    }
+
+    pub fn run_until<F>(&mut self, mut stop: F) -> Result<Round>
+        where F: FnMut(&Round) -> bool
+    {
+        loop {
+            let round = self.round()?;
+            if stop(&round) {
+                return Ok(round);
+            }
+            if self.nodes.values().all(|n| n.halted) {
+                return Err(anyhow!("All nodes halted"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use super::*;
+    use super::super::assemble;
+
+    fn ring() -> Vec<isize> {
+        assemble(indoc!("
+                    in    addr
+            loop:   in    x
+                    eq    x, #-1, tmp
+                    jt    tmp, #loop
+                    in    y
+                    add   addr, #1, dest
+                    eq    dest, #3, tmp
+                    jf    tmp, #send
+                    add   #255, #0, dest
+            send:   out   dest
+                    out   x
+                    add   y, #1, y
+                    out   y
+                    jt    #1, #loop
+            addr:   .data 0
+            x:      .data 0
+            y:      .data 0
+            dest:   .data 0
+            tmp:    .data 0
+        ")).unwrap()
+    }
+
+    #[test]
+    fn routing() {
+        let mut cluster = Cluster::new(&ring(), 3);
+        assert!(!cluster.round().unwrap().idle);
+        assert!(cluster.round().unwrap().idle);
+
+        cluster.send(Packet { dest: 0, x: 7, y: 0 });
+        let round = cluster.round().unwrap();
+        assert_eq!(round.sent, &[
+            Packet { dest: 1, x: 7, y: 1 },
+            Packet { dest: 2, x: 7, y: 2 },
+            Packet { dest: 255, x: 7, y: 3 },
+        ]);
+        assert!(!round.idle);
+        assert_eq!(cluster.undelivered(), &[Packet { dest: 255, x: 7, y: 3 }]);
+        assert!(cluster.round().unwrap().idle);
+    }
+
+    #[test]
+    fn nat_monitor() {
+        let mut cluster = Cluster::new(&ring(), 3);
+        cluster.set_nat(255, 0);
+        cluster.send(Packet { dest: 1, x: 5, y: 10 });
+
+        let round = cluster.run_until(|r| r.nat.is_some()).unwrap();
+        assert_eq!(round.nat, Some(Packet { dest: 0, x: 5, y: 12 }));
+        let round = cluster.run_until(|r| r.nat.is_some()).unwrap();
+        assert_eq!(round.nat, Some(Packet { dest: 0, x: 5, y: 15 }));
+        assert_eq!(cluster.nat_packet(), Some(Packet { dest: 255, x: 5, y: 15 }));
+        assert!(cluster.undelivered().is_empty());
+    }
+
+    #[test]
+    fn demand_schedule() {
+        let mut cluster = Cluster::new(&ring(), 3);
+        cluster.set_schedule(Schedule::Demand);
+        assert!(!cluster.round().unwrap().idle);
+        assert!(cluster.round().unwrap().idle);
+
+        cluster.send(Packet { dest: 1, x: 5, y: 10 });
+        assert_eq!(cluster.round().unwrap().sent, &[Packet { dest: 2, x: 5, y: 11 }]);
+        assert_eq!(cluster.round().unwrap().sent, &[Packet { dest: 255, x: 5, y: 12 }]);
+        assert!(cluster.round().unwrap().idle);
+    }
+
+    #[test]
+    fn halting_and_faults() {
+        let mut cluster = Cluster::new(&[3,0,99], 2);
+        assert!(cluster.run_until(|_| false).is_err());
+        cluster.send(Packet { dest: 1, x: 1, y: 2 });
+        assert_eq!(cluster.undelivered().len(), 1);
+
+        let mut cluster = Cluster::new(&[3,0,104,-1,104,0,104,0,99], 1);
+        assert!(cluster.round().is_err());
+
+        let mut cluster = Cluster::new(&[3,0,42], 1);
+        assert_eq!(cluster.round().unwrap_err().to_string(), "Node 0: Invalid opcode 42 at 2");
+    }
+}
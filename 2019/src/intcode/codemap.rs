@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem;
+use super::{Computer, Param};
+
+const START: u8 = 1;
+const CODE: u8 = 2;
+const DATA: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    Unused,
+    Code,
+    Data,
+    Mixed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeWrite {
+    pub ip: usize,
+    pub addr: usize,
+    pub old: isize,
+    pub new: isize,
+}
+
+impl fmt::Display for CodeWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "write to code at {} from {}: {} -> {}", self.addr, self.ip, self.old, self.new)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CodeMap {
+    flags: BTreeMap<usize, u8>,
+    writes: Vec<CodeWrite>,
+}
+
+impl CodeMap {
+    fn mark(&mut self, addr: usize, flag: u8) {
+        *self.flags.entry(addr).or_insert(0) |= flag;
+    }
+
+    fn flags(&self, addr: usize) -> u8 {
+        self.flags.get(&addr).copied().unwrap_or(0)
+    }
+
+    pub fn region(&self, addr: usize) -> Region {
+        match self.flags(addr) & (CODE | DATA) {
+            0 => Region::Unused,
+            CODE => Region::Code,
+            DATA => Region::Data,
+            _ => Region::Mixed,
+        }
+    }
+
+    pub fn is_code(&self, addr: usize) -> bool {
+        self.flags(addr) & CODE != 0
+    }
+
+    pub fn instructions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.flags.iter().filter(|(_, f)| *f & START != 0).map(|(a, _)| *a)
+    }
+
+    pub fn writes(&self) -> &[CodeWrite] {
+        &self.writes
+    }
+}
+
+impl fmt::Display for CodeMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self.flags.keys().map(|a| a / 64).collect::<BTreeSet<_>>();
+        for row in rows {
+            let last = self.flags.range(row * 64..(row + 1) * 64).next_back().map_or(0, |(a, _)| *a);
+            let cells = (row * 64..=last).map(|addr| match self.region(addr) {
+                Region::Unused => '.',
+                Region::Code => 'c',
+                Region::Data => 'd',
+                Region::Mixed => 'm',
+            });
+            writeln!(f, "{:>6}: {}", row * 64, cells.collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+impl Computer {
+    pub fn track_code(&mut self) {
+        self.code_map = Some(CodeMap::default());
+    }
+
+    pub fn code_map(&self) -> Option<&CodeMap> {
+        self.code_map.as_ref()
+    }
+
+    pub fn take_code_writes(&mut self) -> Vec<CodeWrite> {
+        self.code_map.as_mut().map(|m| mem::take(&mut m.writes)).unwrap_or_default()
+    }
+
+    pub(super) fn code_begin(&mut self) -> Option<(usize, isize)> {
+        let op = self.current_opcode().ok()?;
+        let mut accessed = Vec::<isize>::new();
+        for idx in 1..=op.params() as u32 {
+            match self.param_mode(idx).ok()? {
+                Param::Immediate(_) => {},
+                Param::Position(v) => accessed.push(*v),
+                Param::Relative(v) => accessed.push(self.relative(*v).ok()?),
+            }
+        }
+        let target = self.write_target().ok()?.map(|addr| (addr as usize, self.peek(addr as usize)));
+
+        let (ip, map) = (self.ip, self.code_map.as_mut()?);
+        map.mark(ip, START);
+        (ip..=ip + op.params()).for_each(|addr| map.mark(addr, CODE));
+        accessed.into_iter().filter(|a| *a >= 0).for_each(|addr| map.mark(addr as usize, DATA));
+        target
+    }
+
+    pub(super) fn code_end(&mut self, ip: usize, target: Option<(usize, isize)>) {
+        if let Some((addr, old)) = target {
+            let new = self.peek(addr);
+            if let Some(map) = self.code_map.as_mut().filter(|m| m.is_code(addr)) {
+                map.writes.push(CodeWrite { ip, addr, old, new });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::State;
+
+    #[test]
+    fn regions() {
+        let mut c = Computer::load(&[1002,7,3,7,4,7,99,33]);
+        c.track_code();
+        assert_eq!(c.resume(), State::Halted);
+        let map = c.code_map().unwrap();
+        assert_eq!((0..9).map(|a| map.region(a)).collect::<Vec<_>>(), &[
+            Region::Code, Region::Code, Region::Code, Region::Code,
+            Region::Code, Region::Code, Region::Code, Region::Data, Region::Unused,
+        ]);
+        assert_eq!(map.instructions().collect::<Vec<_>>(), &[0, 4, 6]);
+        assert!(map.writes().is_empty());
+        assert_eq!(map.to_string(), "     0: cccccccd\n");
+
+        let mut c = Computer::load_with(&[109,1,1101,5,0,1000000000000,99], super::super::MemoryModel::Paged);
+        c.track_code();
+        assert_eq!(c.resume(), State::Halted);
+        let map = c.code_map().unwrap();
+        assert_eq!(map.region(1000000000000), Region::Data);
+        assert_eq!(map.to_string(), "     0: ccccccc\n1000000000000: d\n");
+    }
+
+    #[test]
+    fn self_modifying() {
+        let mut c = Computer::load(&[1002,4,3,4,33]);
+        c.track_code();
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.code_map().unwrap().region(4), Region::Mixed);
+        assert!(c.take_code_writes().is_empty());
+
+        let mut c = Computer::load(&[1101,1,1,5,104,0,1101,0,99,4,1105,1,4]);
+        c.track_code();
+        assert_eq!(c.run_until_event(), State::Output(2));
+        assert_eq!(c.run_until_event(), State::Halted);
+        let writes = c.take_code_writes();
+        assert_eq!(writes, &[CodeWrite { ip: 6, addr: 4, old: 104, new: 99 }]);
+        assert_eq!(writes[0].to_string(), "write to code at 4 from 6: 104 -> 99");
+        assert!(c.take_code_writes().is_empty());
+        assert_eq!(c.code_map().unwrap().instructions().collect::<Vec<_>>(), &[0, 4, 6, 10]);
+    }
+}
@@ -0,0 +1,363 @@
+use anyhow::Result;
+use std::sync::Arc;
+use super::arith::{self, Arithmetic, BinaryOp};
+use super::{Computer, Device, Fault, FaultKind, Opcode, Param, State};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Imm,
+    Pos,
+    Rel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Mul,
+    In,
+    Out,
+    Jt,
+    Jf,
+    Lt,
+    Eq,
+    Arb,
+    Hlt,
+    Interpret,
+}
+
+impl From<Opcode> for Op {
+    fn from(op: Opcode) -> Self {
+        match op {
+            Opcode::Add => Op::Add,
+            Opcode::Mul => Op::Mul,
+            Opcode::In => Op::In,
+            Opcode::Out => Op::Out,
+            Opcode::Jt => Op::Jt,
+            Opcode::Jf => Op::Jf,
+            Opcode::Lt => Op::Lt,
+            Opcode::Eq => Op::Eq,
+            Opcode::Arb => Op::Arb,
+            Opcode::Hlt => Op::Hlt,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Instr {
+    op: Op,
+    modes: [Mode; 3],
+}
+
+impl Instr {
+    const INTERPRET: Instr = Instr { op: Op::Interpret, modes: [Mode::Imm; 3] };
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Compiled {
+    instrs: Arc<Vec<Instr>>,
+    invalidations: usize,
+}
+
+impl Compiled {
+    #[inline]
+    fn get(&self, addr: usize) -> Instr {
+        match self.instrs.get(addr) {
+            Some(instr) => *instr,
+            None => Instr::INTERPRET,
+        }
+    }
+
+    fn set(&mut self, addr: usize, instr: Instr) {
+        let instrs = Arc::make_mut(&mut self.instrs);
+        if instrs.len() <= addr {
+            instrs.resize(addr + 1, Instr::INTERPRET);
+        }
+        instrs[addr] = instr;
+    }
+
+    #[inline]
+    pub(super) fn invalidate(&mut self, addr: usize) {
+        if matches!(self.instrs.get(addr), Some(instr) if instr.op != Op::Interpret) {
+            self.invalidate_at(addr);
+        }
+    }
+
+    #[cold]
+    fn invalidate_at(&mut self, addr: usize) {
+        Arc::make_mut(&mut self.instrs)[addr] = Instr::INTERPRET;
+        self.invalidations += 1;
+    }
+}
+
+impl Computer {
+    pub fn compile(&mut self) {
+        let mut compiled = Compiled::default();
+        for addr in 0..self.memory.len() {
+            if let Some(instr) = self.decode_instr(addr) {
+                compiled.set(addr, instr);
+            }
+        }
+        self.compiled = Some(compiled);
+    }
+
+    pub fn is_compiled(&self) -> bool {
+        self.compiled.is_some()
+    }
+
+    pub fn invalidations(&self) -> usize {
+        self.compiled.as_ref().map_or(0, |c| c.invalidations)
+    }
+
+    fn decode_instr(&self, addr: usize) -> Option<Instr> {
+        let op = self.opcode_at(addr).ok()?;
+        if addr + op.params() >= self.memory.len() {
+            return None;
+        }
+        let mut instr = Instr { op: Op::from(op), modes: [Mode::Imm; 3] };
+        for idx in 1..=op.params() as u32 {
+            instr.modes[idx as usize - 1] = match self.param_mode_at(addr, idx).ok()? {
+                Param::Immediate(_) if op.output_param() == Some(idx) => return None,
+                Param::Immediate(_) => Mode::Imm,
+                Param::Position(_) => Mode::Pos,
+                Param::Relative(_) => Mode::Rel,
+            };
+        }
+        Some(instr)
+    }
+
+    pub(super) fn can_run_compiled(&self) -> bool {
+        self.compiled.is_some() && self.trace.is_none() && self.code_map.is_none() && self.profile.is_none() && self.arithmetic != Arithmetic::BigInt && self.wide.is_empty()
+    }
+
+    #[inline]
+    fn load_operand(&self, instr: &Instr, idx: usize) -> Result<isize, Fault> {
+        let value = self.memory[self.ip + idx + 1];
+        match instr.modes[idx] {
+            Mode::Imm => Ok(value),
+            Mode::Pos => self.mem(value).copied(),
+            Mode::Rel => self.mem(self.relative(value)?).copied(),
+        }
+    }
+
+    #[inline]
+    fn store_operand(&mut self, compiled: &mut Compiled, instr: &Instr, idx: usize, value: isize) -> Result<(), Fault> {
+        let pos = match instr.modes[idx] {
+            Mode::Pos => self.memory[self.ip + idx + 1],
+            Mode::Rel => self.relative(self.memory[self.ip + idx + 1])?,
+            Mode::Imm => unreachable!("Immediate output operands are never compiled"),
+        };
+        *self.mem_mut(pos)? = value;
+        compiled.invalidate(pos as usize);
+        Ok(())
+    }
+
+    #[inline]
+    fn combine(&self, op: BinaryOp, instr: &Instr) -> Result<isize, Fault> {
+        let (a, b) = (self.load_operand(instr, 0)?, self.load_operand(instr, 1)?);
+        match self.arithmetic {
+            Arithmetic::Checked => (op.checked)(a, b).ok_or_else(|| self.fault(FaultKind::Overflow)),
+            _ => Ok((op.wrapping)(a, b)),
+        }
+    }
+
+    pub(super) fn run_compiled(&mut self, device: &mut dyn Device) -> Result<State> {
+        let mut compiled = self.compiled.take().unwrap_or_default();
+        let result = loop {
+            if let Some(state) = self.budget_interrupt() {
+                break Ok(state);
+            }
+            match self.execute_with(&mut compiled, device) {
+                Ok(None) => self.charge_budget(),
+                Ok(Some(state)) => break Ok(state),
+                Err(e) => break e.downcast::<Fault>().map(State::Fault),
+            }
+        };
+        self.compiled = Some(compiled);
+        result
+    }
+
+    pub(super) fn execute_compiled(&mut self, device: &mut dyn Device) -> Result<Option<State>> {
+        let mut compiled = self.compiled.take().unwrap_or_default();
+        let result = self.execute_with(&mut compiled, device);
+        self.compiled = Some(compiled);
+        result
+    }
+
+    #[inline]
+    fn execute_with(&mut self, compiled: &mut Compiled, device: &mut dyn Device) -> Result<Option<State>> {
+        let instr = compiled.get(self.ip);
+        self.ip += match instr.op {
+            Op::Add => {
+                let value = self.combine(arith::ADD, &instr)?;
+                self.store_operand(compiled, &instr, 2, value)?;
+                4
+            },
+            Op::Mul => {
+                let value = self.combine(arith::MUL, &instr)?;
+                self.store_operand(compiled, &instr, 2, value)?;
+                4
+            },
+            Op::In => {
+                let value = match device.input() {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                self.store_operand(compiled, &instr, 0, value)?;
+                2
+            },
+            Op::Out => {
+                device.output(self.load_operand(&instr, 0)?)?;
+                2
+            },
+            Op::Jt => if self.load_operand(&instr, 0)? != 0 {
+                self.ip = self.load_operand(&instr, 1)? as usize;
+                0
+            } else {
+                3
+            },
+            Op::Jf => if self.load_operand(&instr, 0)? == 0 {
+                self.ip = self.load_operand(&instr, 1)? as usize;
+                0
+            } else {
+                3
+            },
+            Op::Lt => {
+                let value = self.combine(arith::LT, &instr)?;
+                self.store_operand(compiled, &instr, 2, value)?;
+                4
+            },
+            Op::Eq => {
+                let value = self.combine(arith::EQ, &instr)?;
+                self.store_operand(compiled, &instr, 2, value)?;
+                4
+            },
+            Op::Arb => {
+                let offset = self.load_operand(&instr, 0)?;
+                self.base = match self.arithmetic {
+                    Arithmetic::Checked => self.base.checked_add(offset).ok_or_else(|| self.fault(FaultKind::Overflow))?,
+                    _ => self.base.wrapping_add(offset),
+                };
+                2
+            },
+            Op::Hlt => return Ok(Some(State::Halted)),
+            Op::Interpret => return self.interpret(compiled, device),
+        };
+        Ok(None)
+    }
+
+    #[cold]
+    fn interpret(&mut self, compiled: &mut Compiled, device: &mut dyn Device) -> Result<Option<State>> {
+        if let Some(instr) = self.decode_instr(self.ip) {
+            compiled.set(self.ip, instr);
+        }
+        match self.write_target() {
+            Ok(Some(target)) if target >= 0 => compiled.invalidate(target as usize),
+            _ => {},
+        }
+        self.execute(device)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn compiled(code: &[isize]) -> Computer {
+        let mut c = Computer::load(code);
+        c.compile();
+        c
+    }
+
+    #[test]
+    fn predecode() {
+        let c = compiled(&[1002,4,3,4,33,21101,1,2,-1,99]);
+        let code = c.compiled.as_ref().unwrap();
+        assert_eq!(code.get(0), Instr { op: Op::Mul, modes: [Mode::Pos, Mode::Imm, Mode::Pos] });
+        assert_eq!(code.get(1).op, Op::Out);
+        assert_eq!(code.get(4), Instr::INTERPRET);
+        assert_eq!(code.get(100), Instr::INTERPRET);
+        assert_eq!(c.decode_instr(5).unwrap().modes, [Mode::Imm, Mode::Imm, Mode::Rel]);
+        assert_eq!(c.decode_instr(4), None);
+        assert_eq!(Computer::load(&[11101,1,2,3]).decode_instr(0), None);
+        assert_eq!(Computer::load(&[1,0,0]).decode_instr(0), None);
+    }
+
+    #[test]
+    fn same_results() {
+        let programs: &[&[isize]] = &[
+            &[109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99],
+            &[1102,34915192,34915192,7,4,7,99,0],
+            &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99],
+            &[1,0,0,0,42],
+            &[109,-5,204,2,99],
+            &[1102,3037000500,3037000500,7,4,7,99,0],
+            &[1,-1,2,3,99],
+            &[109,10,22201,-5,-4,-3,1105,1,9,99],
+        ];
+        for code in programs {
+            for input in 7..=9 {
+                let mut plain = Computer::load(code);
+                let mut fast = compiled(code);
+                plain.push_input(input);
+                fast.push_input(input);
+                assert_eq!(fast.resume(), plain.resume());
+                assert_eq!(fast.output(), plain.output());
+                assert_eq!(fast.snapshot(), plain.snapshot());
+            }
+        }
+    }
+
+    #[test]
+    fn relative_overflow() {
+        let programs: &[&[isize]] = &[&[109,9223372036854775807,204,1,99], &[109,9223372036854775807,203,1,99]];
+        for code in programs {
+            for (arithmetic, kind) in [(Arithmetic::Checked, FaultKind::Overflow), (Arithmetic::Wrapping, FaultKind::NegativeAddress(isize::MIN))].iter() {
+                let mut plain = Computer::load(code);
+                let mut fast = compiled(code);
+                for c in [&mut plain, &mut fast].iter_mut() {
+                    c.set_arithmetic(*arithmetic);
+                    c.push_input(1);
+                }
+                assert_eq!(fast.resume(), State::Fault(Fault { ip: 2, kind: *kind }));
+                assert_eq!(plain.resume(), State::Fault(Fault { ip: 2, kind: *kind }));
+            }
+        }
+    }
+
+    #[test]
+    fn self_modifying() {
+        let mut c = compiled(&[1101,0,4,9,1101,0,99,10,99,0,0]);
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.ip(), 8);
+        assert_eq!(c.invalidations(), 0);
+
+        let mut c = compiled(&[1101,1,1,5,104,0,1101,0,99,4,1105,1,4]);
+        assert_eq!(c.run_until_event(), State::Output(2));
+        assert_eq!(c.run_until_event(), State::Halted);
+        assert_eq!(c.invalidations(), 1);
+
+        let mut c = compiled(&[1,0,0,0,99]);
+        c.set_noun_verb(4, 4);
+        assert_eq!(c.invalidations(), 0);
+        assert!(c.run().is_ok());
+        assert_eq!(c.get_result(), 198);
+    }
+
+    #[test]
+    fn shared_between_clones() {
+        let base = compiled(&[3,11,1,11,12,12,4,12,99,0,0,0,5]);
+        let instrs = |c: &Computer| Arc::as_ptr(&c.compiled.as_ref().unwrap().instrs);
+        let mut a = base.clone();
+        a.push_input(3);
+        assert_eq!(a.resume(), State::Halted);
+        assert_eq!(a.output(), &[8]);
+        assert_eq!(instrs(&a), instrs(&base));
+
+        let mut b = base.clone();
+        b.set_noun_verb(12, 12);
+        assert_eq!(b.invalidations(), 1);
+        assert_ne!(instrs(&b), instrs(&base));
+        assert_eq!(base.compiled.as_ref().unwrap().get(2).op, Op::Add);
+        assert_eq!(b.compiled.as_ref().unwrap().get(2), Instr::INTERPRET);
+    }
+}
@@ -0,0 +1,243 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+use super::{CodeWrite, Computer, Opcode, State};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    Address(usize),
+    Opcode(Opcode),
+    Write(usize),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Address(addr) => write!(f, "address {}", addr),
+            Breakpoint::Opcode(op) => write!(f, "opcode {}", op),
+            Breakpoint::Write(addr) => write!(f, "write to {}", addr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop {
+    Step,
+    Breakpoint(Breakpoint),
+    Watch { addr: usize, old: isize, new: isize },
+    CodeWrite(CodeWrite),
+    NeedsInput,
+    Halted,
+    BudgetExhausted,
+    Stopped,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Step => write!(f, "step"),
+            Stop::Breakpoint(bp) => write!(f, "breakpoint on {}", bp),
+            Stop::Watch { addr, old, new } => write!(f, "watch on {}: {} -> {}", addr, old, new),
+            Stop::CodeWrite(write) => write!(f, "{}", write),
+            Stop::NeedsInput => write!(f, "waiting for input"),
+            Stop::Halted => write!(f, "halted"),
+            Stop::BudgetExhausted => write!(f, "budget exhausted"),
+            Stop::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    computer: Computer,
+    breakpoints: Vec<Breakpoint>,
+    watches: BTreeMap<usize, isize>,
+}
+
+impl Debugger {
+    pub fn new(computer: Computer) -> Self {
+        Debugger {
+            computer,
+            breakpoints: Vec::new(),
+            watches: BTreeMap::new(),
+        }
+    }
+
+    pub fn computer(&self) -> &Computer {
+        &self.computer
+    }
+
+    pub fn computer_mut(&mut self) -> &mut Computer {
+        &mut self.computer
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, bp: Breakpoint) -> bool {
+        if self.breakpoints.contains(&bp) {
+            return false;
+        }
+        self.breakpoints.push(bp);
+        true
+    }
+
+    pub fn remove_breakpoint(&mut self, bp: Breakpoint) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|b| b != &bp);
+        self.breakpoints.len() < len
+    }
+
+    pub fn watches(&self) -> impl Iterator<Item = (usize, isize)> + '_ {
+        self.watches.iter().map(|(a, v)| (*a, *v))
+    }
+
+    pub fn watch(&mut self, addr: usize) {
+        self.watches.insert(addr, self.computer.peek(addr));
+    }
+
+    pub fn unwatch(&mut self, addr: usize) -> bool {
+        self.watches.remove(&addr).is_some()
+    }
+
+    fn hit_breakpoint(&self) -> Option<Breakpoint> {
+        let op = self.computer.current_opcode().ok()?;
+        let target = self.computer.write_target().ok().flatten();
+        self.breakpoints.iter().copied().find(|bp| match bp {
+            Breakpoint::Address(addr) => *addr == self.computer.ip,
+            Breakpoint::Opcode(o) => *o == op,
+            Breakpoint::Write(addr) => target == Some(*addr as isize),
+        })
+    }
+
+    fn changed_watch(&mut self) -> Option<Stop> {
+        let mut stop = None;
+        for (addr, value) in self.watches.iter_mut() {
+            let new = self.computer.peek(*addr);
+            if new != *value {
+                stop = stop.or(Some(Stop::Watch { addr: *addr, old: *value, new }));
+                *value = new;
+            }
+        }
+        stop
+    }
+
+    pub fn step(&mut self) -> Result<Stop> {
+        match self.computer.step_queued() {
+            None | Some(State::Output(_)) => {},
+            Some(State::Halted) => return Ok(Stop::Halted),
+            Some(State::NeedsInput) => return Ok(Stop::NeedsInput),
+            Some(State::Fault(fault)) => return Err(fault.into()),
+            Some(State::BudgetExhausted) => return Ok(Stop::BudgetExhausted),
+            Some(State::Stopped) => return Ok(Stop::Stopped),
+        }
+        let watch = self.changed_watch();
+        let write = self.computer.take_code_writes().pop();
+        Ok(watch.or_else(|| write.map(Stop::CodeWrite)).unwrap_or(Stop::Step))
+    }
+
+    pub fn cont(&mut self) -> Result<Stop> {
+        loop {
+            match self.step()? {
+                Stop::Step => {},
+                stop => return Ok(stop),
+            }
+            if let Some(bp) = self.hit_breakpoint() {
+                return Ok(Stop::Breakpoint(bp));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::Budget;
+
+    fn quine() -> Debugger {
+        Debugger::new(Computer::load(&[109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99]))
+    }
+
+    #[test]
+    fn single_step() {
+        let mut d = quine();
+        assert_eq!(d.step().unwrap(), Stop::Step);
+        assert_eq!((d.computer().ip(), d.computer().base()), (2, 1));
+        assert_eq!(d.step().unwrap(), Stop::Step);
+        assert_eq!(d.computer().output(), &[109]);
+        assert_eq!(d.cont().unwrap(), Stop::Halted);
+        assert_eq!(d.step().unwrap(), Stop::Halted);
+        assert_eq!(d.computer().output().len(), 16);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut d = quine();
+        assert!(d.add_breakpoint(Breakpoint::Address(12)));
+        assert!(!d.add_breakpoint(Breakpoint::Address(12)));
+        assert_eq!(d.cont().unwrap(), Stop::Breakpoint(Breakpoint::Address(12)));
+        assert_eq!(d.computer().ip(), 12);
+        assert_eq!(d.cont().unwrap(), Stop::Breakpoint(Breakpoint::Address(12)));
+        assert_eq!(d.computer().output(), &[109,1]);
+        assert!(d.remove_breakpoint(Breakpoint::Address(12)));
+        assert!(!d.remove_breakpoint(Breakpoint::Address(12)));
+
+        assert!(d.add_breakpoint(Breakpoint::Opcode(Opcode::Out)));
+        assert_eq!(d.cont().unwrap(), Stop::Breakpoint(Breakpoint::Opcode(Opcode::Out)));
+        assert_eq!(d.computer().ip(), 2);
+        assert_eq!(d.computer().output(), &[109,1]);
+        assert!(d.remove_breakpoint(Breakpoint::Opcode(Opcode::Out)));
+
+        assert!(d.add_breakpoint(Breakpoint::Write(101)));
+        assert_eq!(d.cont().unwrap(), Stop::Breakpoint(Breakpoint::Write(101)));
+        assert_eq!(d.computer().ip(), 8);
+        assert_eq!(d.breakpoints(), &[Breakpoint::Write(101)]);
+    }
+
+    #[test]
+    fn watches() {
+        let mut d = quine();
+        d.watch(100);
+        assert_eq!(d.watches().collect::<Vec<_>>(), &[(100, 0)]);
+        assert_eq!(d.cont().unwrap(), Stop::Watch { addr: 100, old: 0, new: 1 });
+        assert_eq!(d.computer().ip(), 8);
+        assert_eq!(d.cont().unwrap(), Stop::Watch { addr: 100, old: 1, new: 2 });
+        assert!(d.unwatch(100));
+        assert_eq!(d.cont().unwrap(), Stop::Halted);
+    }
+
+    #[test]
+    fn code_writes() {
+        let mut d = Debugger::new(Computer::load(&[1101,1,1,5,104,0,1101,0,99,4,1105,1,4]));
+        d.computer_mut().track_code();
+        let write = CodeWrite { ip: 6, addr: 4, old: 104, new: 99 };
+        assert_eq!(d.cont().unwrap(), Stop::CodeWrite(write));
+        assert_eq!(d.computer().ip(), 10);
+        assert_eq!(d.cont().unwrap(), Stop::Halted);
+        assert_eq!(Stop::CodeWrite(write).to_string(), "write to code at 4 from 6: 104 -> 99");
+    }
+
+    #[test]
+    fn waiting_for_input() {
+        let mut d = Debugger::new(Computer::load(&[3,5,4,5,99,0]));
+        assert_eq!(d.step().unwrap(), Stop::NeedsInput);
+        assert_eq!(d.cont().unwrap(), Stop::NeedsInput);
+        assert_eq!(d.computer().ip(), 0);
+        d.computer_mut().push_input(7);
+        assert_eq!(d.cont().unwrap(), Stop::Halted);
+        assert_eq!(d.computer().output(), &[7]);
+        assert_eq!(Stop::NeedsInput.to_string(), "waiting for input");
+    }
+
+    #[test]
+    fn budget() {
+        let mut d = Debugger::new(Computer::load(&[1001,7,1,7,1105,1,0,0]));
+        d.computer_mut().set_budget(Budget::steps(5));
+        assert_eq!(d.cont().unwrap(), Stop::BudgetExhausted);
+        assert_eq!((d.computer().ip(), d.computer().peek(7)), (4, 3));
+        d.computer_mut().clear_budget();
+        assert_eq!(d.step().unwrap(), Stop::Step);
+        assert_eq!(d.computer().ip(), 0);
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use std::collections::VecDeque;
+
+pub trait Device {
+    fn input(&mut self) -> Option<isize>;
+    fn output(&mut self, value: isize) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Queues {
+    pub input: VecDeque<isize>,
+    pub output: Vec<isize>,
+}
+
+impl Device for Queues {
+    fn input(&mut self) -> Option<isize> {
+        self.input.pop_front()
+    }
+
+    fn output(&mut self, value: isize) -> Result<()> {
+        self.output.push(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::ensure;
+    use super::*;
+    use super::super::{Computer, State};
+
+    struct Doubler {
+        values: Vec<isize>,
+        results: Vec<isize>,
+    }
+
+    impl Device for Doubler {
+        fn input(&mut self) -> Option<isize> {
+            self.values.pop()
+        }
+
+        fn output(&mut self, value: isize) -> Result<()> {
+            ensure!(value % 2 == 0, "Odd output");
+            self.results.push(value);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn attached_device() {
+        let mut c = Computer::load(&[3,11,1002,11,2,11,4,11,1105,1,0,0]);
+        let mut d = Doubler { values: vec![3, 2, 1], results: Vec::new() };
+        assert_eq!(c.run_with(&mut d).unwrap(), State::NeedsInput);
+        assert_eq!(d.results, &[2, 4, 6]);
+        assert_eq!(c.ip(), 0);
+
+        d.values.push(5);
+        assert_eq!(c.run_with(&mut d).unwrap(), State::NeedsInput);
+        assert_eq!(d.results, &[2, 4, 6, 10]);
+
+        let mut c = Computer::load(&[104,3,99]);
+        assert!(c.run_with(&mut d).is_err());
+    }
+
+    #[test]
+    fn cooperative_queues() {
+        let mut c = Computer::load(&[3,9,1001,9,1,9,4,9,99,0]);
+        assert_eq!(c.resume(), State::NeedsInput);
+        assert_eq!(c.ip(), 0);
+        assert!(c.output().is_empty());
+        c.push_input(41);
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.pop_output().unwrap(), 42);
+        assert_eq!(c.resume(), State::Halted);
+    }
+}
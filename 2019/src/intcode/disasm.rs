@@ -0,0 +1,189 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+use super::{Computer, Opcode, Param};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item<'a> {
+    Code(Opcode, Vec<Param<'a>>),
+    Data(isize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line<'a> {
+    pub addr: usize,
+    pub item: Item<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listing<'a> {
+    pub lines: Vec<Line<'a>>,
+}
+
+impl Item<'_> {
+    pub fn size(&self) -> usize {
+        match self {
+            Item::Code(_, params) => params.len() + 1,
+            Item::Data(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for Item<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Item::Code(op, params) if params.is_empty() => write!(f, "{}", op),
+            Item::Code(op, params) => {
+                write!(f, "{:<5} ", op)?;
+                for (i, p) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", p)?;
+                }
+                Ok(())
+            },
+            Item::Data(v) => write!(f, "{:<5} {}", ".data", v),
+        }
+    }
+}
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6}: {}", self.addr, self.item)
+    }
+}
+
+impl fmt::Display for Listing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Computer {
+    pub fn decode_at(&self, addr: usize) -> Result<Item<'_>> {
+        let op = self.opcode_at(addr)?;
+        let params = (1..=op.params() as u32)
+            .map(|idx| self.param_mode_at(addr, idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Item::Code(op, params))
+    }
+
+    pub(super) fn trace_code(&self) -> BTreeMap<usize, Item<'_>> {
+        let mut code = BTreeMap::<usize, Item>::new();
+        let mut pending = vec![0usize];
+        if let Some(map) = self.code_map() {
+            pending.extend(map.instructions());
+        }
+
+        while let Some(addr) = pending.pop() {
+            if code.contains_key(&addr) {
+                continue;
+            }
+            let item = match self.decode_at(addr) {
+                Ok(item) => item,
+                Err(_) => continue,
+            };
+            let next = addr + item.size();
+            if let Item::Code(op, params) = &item {
+                match op {
+                    Opcode::Hlt => {},
+                    Opcode::Jt | Opcode::Jf => {
+                        if let Param::Immediate(target) = params[1] {
+                            if *target >= 0 {
+                                pending.push(*target as usize);
+                            }
+                        }
+                        match (op, params[0]) {
+                            (Opcode::Jt, Param::Immediate(c)) if *c != 0 => {},
+                            (Opcode::Jf, Param::Immediate(c)) if *c == 0 => {},
+                            _ => pending.push(next),
+                        }
+                    },
+                    _ => pending.push(next),
+                }
+            }
+            code.insert(addr, item);
+        }
+        code
+    }
+
+    pub fn disassemble(&self) -> Listing<'_> {
+        let mut code = self.trace_code();
+        let mut lines = Vec::<Line>::new();
+        let mut addr = 0;
+
+        while addr < self.memory.len() {
+            let item = match code.remove(&addr) {
+                Some(item) if addr + item.size() <= self.memory.len() => item,
+                _ => Item::Data(self.memory[addr]),
+            };
+            let len = item.size();
+            lines.push(Line { addr, item });
+            addr += len;
+        }
+        Listing { lines }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_instruction() {
+        let c = Computer::load(&[1002,4,3,4,33]);
+        assert_eq!(c.decode_at(0).unwrap(), Item::Code(Opcode::Mul, vec![Param::Position(&4), Param::Immediate(&3), Param::Position(&4)]));
+        assert!(c.decode_at(4).is_err());
+        assert!(c.decode_at(5).is_err());
+
+        let c = Computer::load(&[21107,1,2]);
+        assert!(c.decode_at(0).is_err());
+    }
+
+    #[test]
+    fn code_and_data() {
+        let c = Computer::load(&[3,9,8,9,10,9,4,9,99,-1,8]);
+        let listing = c.disassemble();
+        assert_eq!(listing.lines.iter().map(|l| l.addr).collect::<Vec<_>>(), &[0,2,6,8,9,10]);
+        assert_eq!(listing.lines[4].item, Item::Data(-1));
+        assert_eq!(listing.lines[5].item, Item::Data(8));
+        assert_eq!(listing.to_string(), concat!(
+            "     0: in    9\n",
+            "     2: eq    9, 10, 9\n",
+            "     6: out   9\n",
+            "     8: hlt\n",
+            "     9: .data -1\n",
+            "    10: .data 8\n",
+        ));
+    }
+
+    #[test]
+    fn jump_targets() {
+        let c = Computer::load(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9]);
+        let listing = c.disassemble();
+        assert_eq!(listing.lines.iter().map(|l| l.addr).collect::<Vec<_>>(), &[0,2,5,9,11,12,13,14,15]);
+        assert_eq!(listing.lines[1].to_string(), "     2: jf    12, 15");
+
+        let c = Computer::load(&[1105,1,4,7,109,-3,204,-1,99]);
+        let listing = c.disassemble();
+        assert_eq!(listing.lines.iter().map(|l| l.addr).collect::<Vec<_>>(), &[0,3,4,6,8]);
+        assert_eq!(listing.lines[1].item, Item::Data(7));
+        assert_eq!(listing.lines[2].to_string(), "     4: arb   #-3");
+        assert_eq!(listing.lines[3].to_string(), "     6: out   rel-1");
+    }
+
+    #[test]
+    fn executed_code() {
+        let mut c = Computer::load(&[6,8,9,99,104,42,99,0,0,4]);
+        assert_eq!(c.disassemble().lines.iter().map(|l| l.addr).collect::<Vec<_>>(), &[0,3,4,5,6,7,8,9]);
+        c.track_code();
+        assert!(c.run().is_ok());
+        let listing = c.disassemble();
+        assert_eq!(listing.lines.iter().map(|l| l.addr).collect::<Vec<_>>(), &[0,3,4,6,7,8,9]);
+        assert_eq!(listing.lines[2].to_string(), "     4: out   #42");
+    }
+}
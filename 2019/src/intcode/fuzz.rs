@@ -0,0 +1,386 @@
+use std::fmt;
+use super::{Budget, Computer, Queues, State};
+
+const OPCODES: [isize; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+const MEMORY_LIMIT: usize = 1 << 16;
+
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, from: isize, to: isize) -> isize {
+        from + self.below((to - from + 1) as usize) as isize
+    }
+
+    fn chance(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub code: Vec<isize>,
+    pub input: Vec<isize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum End {
+    Halted,
+    NeedsInput,
+    Fault(usize),
+    StepLimit,
+    MemoryLimit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub end: End,
+    pub steps: usize,
+    pub memory: Vec<isize>,
+    pub output: Vec<isize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub mode: &'static str,
+    pub case: Case,
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |values: &[isize]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",");
+        writeln!(f, "{} computer diverges from reference", self.mode)?;
+        writeln!(f, "code: {}", join(&self.case.code))?;
+        writeln!(f, "input: {}", join(&self.case.input))?;
+        writeln!(f, "expected: {:?} after {} steps, output {}", self.expected.end, self.expected.steps, join(&self.expected.output))?;
+        writeln!(f, "actual: {:?} after {} steps, output {}", self.actual.end, self.actual.steps, join(&self.actual.output))?;
+        writeln!(f, "expected memory: {}", join(&self.expected.memory))?;
+        write!(f, "actual memory: {}", join(&self.actual.memory))
+    }
+}
+
+fn trimmed(mut memory: Vec<isize>) -> Vec<isize> {
+    while memory.last() == Some(&0) {
+        memory.pop();
+    }
+    memory
+}
+
+pub fn generate(rng: &mut Rng) -> Case {
+    let count = rng.range(1, 24) as usize;
+    let mut ops = (0..count)
+        .map(|_| match rng.below(60) {
+            0 => 99,
+            1 => rng.range(10, 98),
+            _ => OPCODES[rng.below(OPCODES.len())],
+        })
+        .collect::<Vec<_>>();
+    ops.push(99);
+    let params = |op: isize| match op {
+        1 | 2 | 7 | 8 => 3,
+        5 | 6 => 2,
+        3 | 4 | 9 => 1,
+        _ => 0,
+    };
+    let mut starts = Vec::<isize>::new();
+    let mut len = 0;
+    for op in &ops {
+        starts.push(len);
+        len += 1 + params(*op);
+    }
+
+    let mut code = Vec::<isize>::new();
+    for op in ops {
+        let mut modes = 0;
+        let mut operands = Vec::<isize>::new();
+        for idx in 1..=params(op) {
+            let output = matches!((op, idx), (1, 3) | (2, 3) | (7, 3) | (8, 3) | (3, 1));
+            let mode = match rng.below(40) {
+                0 => rng.range(3, 9),
+                _ if output => [0, 2][rng.below(2)],
+                _ => rng.range(0, 2),
+            };
+            let value = match (mode, op, idx) {
+                (1, 5, 2) | (1, 6, 2) => starts[rng.below(starts.len())],
+                (1, 9, 1) => rng.range(-4, 8),
+                (1, _, _) if rng.chance(30) => [isize::MAX, isize::MIN, 1 << 40][rng.below(3)],
+                (1, _, _) => rng.range(-3, 12),
+                (2, _, _) => rng.range(-4, len + 4),
+                _ => rng.range(0, len + 8),
+            };
+            modes += mode * 10isize.pow(idx as u32 + 1);
+            operands.push(value);
+        }
+        code.push(op + modes);
+        code.extend(operands);
+    }
+    let input = (0..rng.below(6)).map(|_| rng.range(-5, 12)).collect();
+    Case { code, input }
+}
+
+struct Reference {
+    memory: Vec<isize>,
+    ip: usize,
+    base: isize,
+}
+
+impl Reference {
+    fn fetch(&self, addr: usize) -> Option<isize> {
+        self.memory.get(addr).copied()
+    }
+
+    fn fits(&self, idx: usize) -> bool {
+        !matches!(self.address(idx), Some(Some(addr)) if addr >= MEMORY_LIMIT)
+    }
+
+    fn address(&self, idx: usize) -> Option<Option<usize>> {
+        let mode = self.fetch(self.ip)? / [100, 1000, 10000][idx - 1] % 10;
+        let value = self.fetch(self.ip + idx)?;
+        let addr = match mode {
+            0 => value,
+            1 => return Some(None),
+            2 => self.base + value,
+            _ => return None,
+        };
+        if addr < 0 {
+            None
+        } else {
+            Some(Some(addr as usize))
+        }
+    }
+
+    fn read(&self, idx: usize) -> Option<isize> {
+        match self.address(idx)? {
+            Some(addr) => Some(self.memory.get(addr).copied().unwrap_or(0)),
+            None => self.fetch(self.ip + idx),
+        }
+    }
+
+    fn write(&mut self, idx: usize, value: isize) -> Option<()> {
+        let addr = self.address(idx)??;
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] = value;
+        Some(())
+    }
+
+    fn step(&mut self, input: &mut Vec<isize>, output: &mut Vec<isize>) -> Option<Option<End>> {
+        let op = self.fetch(self.ip)? % 100;
+        let target = match op {
+            1 | 2 | 7 | 8 => Some(3),
+            3 => Some(1),
+            _ => None,
+        };
+        if target.is_some_and(|idx| !self.fits(idx)) {
+            return Some(Some(End::MemoryLimit));
+        }
+        match op {
+            1 | 2 | 7 | 8 => {
+                let (a, b) = (self.read(1)?, self.read(2)?);
+                let value = match op {
+                    1 => a.checked_add(b)?,
+                    2 => a.checked_mul(b)?,
+                    7 => (a < b) as isize,
+                    _ => (a == b) as isize,
+                };
+                self.write(3, value)?;
+                self.ip += 4;
+            },
+            3 => {
+                if input.is_empty() {
+                    return Some(Some(End::NeedsInput));
+                }
+                let value = input.remove(0);
+                self.write(1, value)?;
+                self.ip += 2;
+            },
+            4 => {
+                output.push(self.read(1)?);
+                self.ip += 2;
+            },
+            5 | 6 => {
+                if (self.read(1)? != 0) == (op == 5) {
+                    self.ip = self.read(2)? as usize;
+                } else {
+                    self.ip += 3;
+                }
+            },
+            9 => {
+                self.base = self.base.checked_add(self.read(1)?)?;
+                self.ip += 2;
+            },
+            99 => return Some(Some(End::Halted)),
+            _ => return None,
+        }
+        Some(None)
+    }
+}
+
+pub fn reference(case: &Case, limit: usize) -> Outcome {
+    let mut machine = Reference { memory: case.code.clone(), ip: 0, base: 0 };
+    let mut input = case.input.clone();
+    let mut output = Vec::<isize>::new();
+    let mut steps = 0;
+    let end = loop {
+        if steps == limit {
+            break End::StepLimit;
+        }
+        match machine.step(&mut input, &mut output) {
+            Some(None) => steps += 1,
+            Some(Some(end)) => break end,
+            None => break End::Fault(machine.ip),
+        }
+    };
+    Outcome { end, steps, memory: trimmed(machine.memory), output }
+}
+
+impl Computer {
+    pub fn run_limited(&mut self, input: &[isize], limit: usize) -> Outcome {
+        let mut queues = Queues { input: input.iter().copied().collect(), output: Vec::new() };
+        self.set_budget(Budget::steps(limit as u64));
+        let end = match self.run_with(&mut queues).expect("Queues never fail") {
+            State::Halted => End::Halted,
+            State::NeedsInput => End::NeedsInput,
+            State::Fault(fault) => End::Fault(fault.ip),
+            State::BudgetExhausted => End::StepLimit,
+            State::Stopped => unreachable!("Fuzzing runs without a stop flag"),
+            State::Output(_) => unreachable!("Queued output never interrupts"),
+        };
+        let steps = limit - self.clear_budget().and_then(|b| b.remaining_steps()).unwrap_or(0) as usize;
+        Outcome { end, steps, memory: trimmed(self.memory.clone()), output: queues.output }
+    }
+}
+
+pub fn check(case: &Case, limit: usize) -> Option<Mismatch> {
+    let expected = reference(case, limit);
+    if expected.end == End::MemoryLimit {
+        return None;
+    }
+    let mut compiled = Computer::load(&case.code);
+    compiled.compile();
+    for (mode, mut computer) in [("interpreted", Computer::load(&case.code)), ("compiled", compiled)] {
+        let actual = computer.run_limited(&case.input, limit);
+        if actual != expected {
+            return Some(Mismatch { mode, case: case.clone(), expected, actual });
+        }
+    }
+    None
+}
+
+pub fn minimize<F: Fn(&Case) -> bool>(case: &Case, fails: F) -> Case {
+    let mut best = case.clone();
+    let mut chunk = (best.code.len() / 2).max(1);
+    loop {
+        let mut improved = false;
+        let mut start = 0;
+        while start < best.code.len() {
+            let mut candidate = best.clone();
+            candidate.code.drain(start..(start + chunk).min(best.code.len()));
+            if !candidate.code.is_empty() && fails(&candidate) {
+                best = candidate;
+                improved = true;
+            } else {
+                start += if chunk > 4 { chunk } else { 1 };
+            }
+        }
+        for idx in (0..best.input.len()).rev() {
+            let mut candidate = best.clone();
+            candidate.input.remove(idx);
+            if fails(&candidate) {
+                best = candidate;
+                improved = true;
+            }
+        }
+        for idx in 0..best.code.len() {
+            for value in [0, best.code[idx] / 2].iter() {
+                let mut candidate = best.clone();
+                candidate.code[idx] = *value;
+                if *value != best.code[idx] && fails(&candidate) {
+                    best = candidate;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            if chunk == 1 {
+                return best;
+            }
+            chunk = if chunk > 8 { chunk / 2 } else { chunk - 1 };
+        }
+    }
+}
+
+pub fn fuzz(seed: u64, iterations: usize, limit: usize) -> Option<Mismatch> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..iterations {
+        let case = generate(&mut rng);
+        if check(&case, limit).is_some() {
+            let case = minimize(&case, |c| check(c, limit).is_some());
+            return check(&case, limit);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reference_examples() {
+        let case = Case { code: vec![3,9,8,9,10,9,4,9,99,-1,8], input: vec![8] };
+        let outcome = reference(&case, 100);
+        assert_eq!((outcome.end, outcome.steps, outcome.output), (End::Halted, 3, vec![1]));
+        assert_eq!(reference(&Case { code: vec![1105,1,0], input: vec![] }, 50).end, End::StepLimit);
+        assert_eq!(reference(&Case { code: vec![3,0,99], input: vec![] }, 50).end, End::NeedsInput);
+        assert_eq!(reference(&Case { code: vec![1,0,0,0,55], input: vec![] }, 50).end, End::Fault(4));
+    }
+
+    #[test]
+    fn generated_programs() {
+        let mut rng = Rng::new(7);
+        let cases = (0..100).map(|_| generate(&mut rng)).collect::<Vec<_>>();
+        assert!(cases.iter().all(|c| c.code.last() == Some(&99)));
+        let ends = cases.iter().map(|c| reference(c, 200).end).collect::<Vec<_>>();
+        for end in [End::Halted, End::NeedsInput, End::StepLimit].iter() {
+            assert!(ends.contains(end));
+        }
+        assert!(ends.iter().any(|e| matches!(e, End::Fault(_))));
+    }
+
+    #[test]
+    fn differential() {
+        if let Some(mismatch) = fuzz(2019, 2000, 500) {
+            panic!("{}", mismatch);
+        }
+    }
+
+    #[test]
+    fn minimization() {
+        let case = Case { code: vec![1101,3,4,20,104,42,1002,20,3,21,4,21,99], input: vec![1, 2] };
+        let fails = |c: &Case| reference(c, 100).output.contains(&42);
+        assert!(fails(&case));
+        assert_eq!(minimize(&case, fails), Case { code: vec![104,42], input: vec![] });
+    }
+}
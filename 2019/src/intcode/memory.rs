@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryModel {
+    Dense,
+    Paged,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pages {
+    pages: HashMap<usize, Box<[isize]>>,
+}
+
+impl Pages {
+    pub fn get(&self, addr: usize) -> Option<&isize> {
+        self.pages.get(&(addr / PAGE_SIZE)).map(|page| &page[addr % PAGE_SIZE])
+    }
+
+    pub fn get_mut(&mut self, addr: usize) -> &mut isize {
+        let page = self.pages.entry(addr / PAGE_SIZE).or_insert_with(|| vec![0; PAGE_SIZE].into_boxed_slice());
+        &mut page[addr % PAGE_SIZE]
+    }
+
+    pub fn insert(&mut self, start: usize, values: &[isize]) {
+        self.get_mut(start);
+        for (i, value) in values.iter().enumerate() {
+            *self.get_mut(start + i) = *value;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &[isize])> {
+        let mut pages = self.pages.iter().map(|(idx, page)| (idx * PAGE_SIZE, &page[..])).collect::<Vec<_>>();
+        pages.sort_by_key(|(start, _)| *start);
+        pages.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::{Computer, State};
+
+    #[test]
+    fn pages() {
+        let mut p = Pages::default();
+        assert_eq!(p.iter().count(), 0);
+        assert_eq!(p.get(5000), None);
+        *p.get_mut(5000) = 3;
+        *p.get_mut(1 << 40) = 4;
+        p.insert(1 << 20, &[]);
+        assert_eq!(p.get(5000), Some(&3));
+        assert_eq!(p.get(5001), Some(&0));
+        assert_eq!(p.get(1 << 40), Some(&4));
+        assert_eq!(p.get(1 << 20), Some(&0));
+        assert_eq!(p.iter().map(|(s, page)| (s, page.len())).collect::<Vec<_>>(), &[(4096, PAGE_SIZE), (1 << 20, PAGE_SIZE), (1 << 40, PAGE_SIZE)]);
+    }
+
+    #[test]
+    fn huge_addresses() {
+        let code = [1101,7,0,1000000000000,4,1000000000000,109,1000000000001,22101,1,-1,0,204,0,99];
+        let mut c = Computer::load_with(&code, MemoryModel::Paged);
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.output(), &[7, 8]);
+        assert_eq!(c.memory_len(), code.len());
+        assert_eq!(c.peek(1000000000000), 7);
+        assert_eq!(c.peek(1000000000001), 8);
+        assert_eq!(c.peek(2000000000000), 0);
+    }
+
+    #[test]
+    fn same_as_dense() {
+        let code = [203,1985,9,9,109,19,204,-34,99,2000];
+        let mut dense = Computer::load(&code);
+        let mut paged = Computer::load_with(&code, MemoryModel::Paged);
+        for c in [&mut dense, &mut paged].iter_mut() {
+            c.push_input(5);
+            assert_eq!(c.resume(), State::Halted);
+            assert_eq!(c.output(), &[5]);
+            assert_eq!(c.peek(1985), 5);
+        }
+        assert_eq!(dense.memory_len(), 1986);
+        assert_eq!(paged.memory_len(), 10);
+
+        let code = [1101,0,0,3000,1106,0,3000];
+        let mut c = Computer::load_with(&code, MemoryModel::Paged);
+        assert!(c.run().is_err());
+        assert_eq!(c.ip(), 3000);
+    }
+}
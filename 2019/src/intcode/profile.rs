@@ -0,0 +1,260 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use super::{Computer, Opcode, Param};
+
+const TOP: usize = 10;
+const HEAT: &[u8] = b".123456789";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Sample {
+    ip: usize,
+    op: Opcode,
+    base: isize,
+    reads: Vec<usize>,
+    write: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    instructions: u64,
+    addresses: BTreeMap<usize, u64>,
+    opcodes: BTreeMap<Opcode, u64>,
+    loops: BTreeMap<(usize, usize), u64>,
+    reads: BTreeMap<usize, u64>,
+    writes: BTreeMap<usize, u64>,
+    inputs: u64,
+    outputs: u64,
+    stack: Vec<usize>,
+    bases: Vec<isize>,
+    stacks: BTreeMap<Vec<usize>, u64>,
+    written: Option<isize>,
+    call: Option<usize>,
+}
+
+fn count<K: Ord>(map: &mut BTreeMap<K, u64>, key: K) {
+    *map.entry(key).or_insert(0) += 1;
+}
+
+fn hottest<K: Copy>(map: &BTreeMap<K, u64>) -> Vec<(K, u64)> {
+    let mut entries = map.iter().map(|(k, c)| (*k, *c)).collect::<Vec<_>>();
+    entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+    entries
+}
+
+fn heatmap(map: &BTreeMap<usize, u64>) -> String {
+    let max = map.values().copied().max().unwrap_or(0) as f64;
+    let rows = map.keys().map(|a| a / 64).collect::<BTreeSet<_>>();
+    let mut result = String::new();
+    for row in rows {
+        let last = map.range(row * 64..(row + 1) * 64).next_back().map_or(0, |(a, _)| *a);
+        let cells = (row * 64..=last).map(|addr| match map.get(&addr) {
+            Some(c) => HEAT[(1.0 + (*c as f64).ln() / max.ln().max(1.0) * 8.0).round() as usize] as char,
+            None => HEAT[0] as char,
+        });
+        result += &format!("{:>6}: {}\n", row * 64, cells.collect::<String>());
+    }
+    result
+}
+
+impl Profile {
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn executions(&self, addr: usize) -> u64 {
+        self.addresses.get(&addr).copied().unwrap_or(0)
+    }
+
+    pub fn opcode(&self, op: Opcode) -> u64 {
+        self.opcodes.get(&op).copied().unwrap_or(0)
+    }
+
+    pub fn hot_addresses(&self) -> Vec<(usize, u64)> {
+        hottest(&self.addresses)
+    }
+
+    pub fn hot_loops(&self) -> Vec<((usize, usize), u64)> {
+        hottest(&self.loops)
+    }
+
+    pub fn reads(&self, addr: usize) -> u64 {
+        self.reads.get(&addr).copied().unwrap_or(0)
+    }
+
+    pub fn writes(&self, addr: usize) -> u64 {
+        self.writes.get(&addr).copied().unwrap_or(0)
+    }
+
+    pub fn inputs(&self) -> u64 {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> u64 {
+        self.outputs
+    }
+
+    pub fn read_heatmap(&self) -> String {
+        heatmap(&self.reads)
+    }
+
+    pub fn write_heatmap(&self) -> String {
+        heatmap(&self.writes)
+    }
+
+    pub fn folded(&self) -> String {
+        self.stacks.iter()
+            .map(|(stack, count)| {
+                let frames = stack.iter().map(|entry| format!(";fn_{}", entry)).collect::<String>();
+                format!("main{} {}\n", frames, count)
+            })
+            .collect()
+    }
+
+    pub fn save_folded<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Ok(fs::write(path, self.folded())?)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = |c: u64| c as f64 * 100.0 / self.instructions.max(1) as f64;
+        writeln!(f, "instructions: {}", self.instructions)?;
+        writeln!(f, "inputs: {}  outputs: {}", self.inputs, self.outputs)?;
+        writeln!(f, "opcodes:")?;
+        for (op, c) in hottest(&self.opcodes) {
+            writeln!(f, "  {:<5} {:>12} {:>6.2}%", op, c, percent(c))?;
+        }
+        writeln!(f, "hot addresses:")?;
+        for (addr, c) in self.hot_addresses().into_iter().take(TOP) {
+            writeln!(f, "  {:>6} {:>12} {:>6.2}%", addr, c, percent(c))?;
+        }
+        writeln!(f, "hot loops:")?;
+        for ((from, to), c) in self.hot_loops().into_iter().take(TOP) {
+            writeln!(f, "  {:>6} -> {:<6} {:>12}", from, to, c)?;
+        }
+        writeln!(f, "reads:")?;
+        write!(f, "{}", self.read_heatmap())?;
+        writeln!(f, "writes:")?;
+        write!(f, "{}", self.write_heatmap())
+    }
+}
+
+impl Computer {
+    pub fn start_profile(&mut self) {
+        self.profile = Some(Profile::default());
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    pub(super) fn profile_begin(&self) -> Option<Sample> {
+        let op = self.current_opcode().ok()?;
+        let mut reads = Vec::<usize>::new();
+        for idx in (1..=op.params() as u32).filter(|idx| op.output_param() != Some(*idx)) {
+            match self.param_mode(idx).ok()? {
+                Param::Immediate(_) => {},
+                Param::Position(v) => reads.push(*v as usize),
+                Param::Relative(v) => reads.push(self.relative(*v).ok()? as usize),
+            }
+        }
+        let write = self.write_target().ok()?.map(|addr| addr as usize);
+        Some(Sample { ip: self.ip, op, base: self.base, reads, write })
+    }
+
+    pub(super) fn profile_end(&mut self, sample: Sample) {
+        let written = sample.write.map(|addr| self.peek(addr));
+        let (ip, base, profile) = match self.profile.as_mut() {
+            Some(profile) => (self.ip, self.base, profile),
+            None => return,
+        };
+        match sample.op {
+            Opcode::Jt | Opcode::Jf if ip != sample.ip + 3 => {
+                profile.call = Some(ip).filter(|_| profile.written == Some(sample.ip as isize + 3));
+            },
+            Opcode::Arb if base > sample.base => if let Some(entry) = profile.call.take() {
+                profile.stack.push(entry);
+                profile.bases.push(sample.base);
+            },
+            _ => {},
+        }
+        if written.is_some() {
+            profile.written = written;
+        }
+
+        profile.instructions += 1;
+        count(&mut profile.addresses, sample.ip);
+        count(&mut profile.opcodes, sample.op);
+        sample.reads.into_iter().for_each(|addr| count(&mut profile.reads, addr));
+        sample.write.into_iter().for_each(|addr| count(&mut profile.writes, addr));
+        match profile.stacks.get_mut(profile.stack.as_slice()) {
+            Some(c) => *c += 1,
+            None => { profile.stacks.insert(profile.stack.clone(), 1); },
+        }
+        match sample.op {
+            Opcode::In => profile.inputs += 1,
+            Opcode::Out => profile.outputs += 1,
+            Opcode::Jt | Opcode::Jf if ip <= sample.ip => count(&mut profile.loops, (sample.ip, ip)),
+            _ => {},
+        }
+
+        while profile.bases.last().is_some_and(|b| base <= *b) {
+            profile.bases.pop();
+            profile.stack.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::State;
+
+    #[test]
+    fn counts() {
+        let mut c = Computer::load(&[3,13,1001,13,-1,13,4,13,1005,13,2,99,7,0]);
+        c.start_profile();
+        c.push_input(3);
+        assert_eq!(c.resume(), State::Halted);
+        let p = c.profile().unwrap();
+        assert_eq!(p.instructions(), 11);
+        assert_eq!((p.executions(0), p.executions(2), p.executions(11)), (1, 3, 1));
+        assert_eq!((p.opcode(Opcode::Add), p.opcode(Opcode::Jt), p.opcode(Opcode::Mul)), (3, 3, 0));
+        assert_eq!(p.hot_loops(), &[((8, 2), 2)]);
+        assert_eq!((p.reads(13), p.writes(13), p.reads(12)), (9, 4, 0));
+        assert_eq!((p.inputs(), p.outputs()), (1, 3));
+        assert_eq!(p.write_heatmap(), "     0: .............9\n");
+        assert_eq!(p.read_heatmap(), "     0: .............9\n");
+        assert!(p.to_string().starts_with("instructions: 11\ninputs: 1  outputs: 3\nopcodes:\n  add              3  27.27%\n"));
+    }
+
+    #[test]
+    fn folded_stacks() {
+        let code = [
+            1105,1,14,
+            109,2, 21101,0,0,-1, 109,-2, 2106,0,0,
+            21101,21,0,0, 1105,1,3,
+            21101,28,0,0, 1105,1,3, 99,
+        ];
+        let mut c = Computer::load(&code);
+        c.start_profile();
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.profile().unwrap().folded(), "main 8\nmain;fn_3 6\n");
+
+        let code = [
+            109,100, 21101,9,0,0, 1105,1,10, 99,
+            1101,1,1,30, 109,2, 109,-2, 2106,0,0,
+        ];
+        let mut c = Computer::load(&code);
+        c.start_profile();
+        assert_eq!(c.resume(), State::Halted);
+        assert_eq!(c.profile().unwrap().folded(), "main 6\nmain;fn_10 2\n");
+    }
+}
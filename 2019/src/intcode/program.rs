@@ -0,0 +1,175 @@
+use anyhow::Result;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use super::Computer;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramError {
+    pub line: usize,
+    pub column: usize,
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {} (token {})", self.line, self.column, self.message, self.index)
+    }
+}
+
+impl Error for ProgramError {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Program {
+    code: Vec<isize>,
+}
+
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut start = None;
+    let mut end = 0;
+    for (pos, ch) in text.char_indices().chain(std::iter::once((text.len(), ','))) {
+        match ch {
+            ',' => {
+                let (text, line, column) = match start.take() {
+                    Some((from, line, column)) => (&text[from..end], line, column),
+                    None => ("", line, column),
+                };
+                tokens.push(Token { text, line, column });
+            },
+            ch if ch.is_whitespace() => {},
+            ch => {
+                if start.is_none() {
+                    start = Some((pos, line, column));
+                }
+                end = pos + ch.len_utf8();
+            },
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    if tokens.len() > 1 && tokens.last().is_some_and(|t| t.text.is_empty()) {
+        tokens.pop();
+    }
+    tokens
+}
+
+impl Program {
+    pub fn new(code: Vec<isize>) -> Self {
+        Program { code }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ProgramError> {
+        if text.trim().is_empty() {
+            return Err(ProgramError { line: 1, column: 1, index: 0, message: "Empty program".to_string() });
+        }
+        let tokens = tokens(text);
+        let mut code = Vec::with_capacity(tokens.len());
+        for (index, token) in tokens.iter().enumerate() {
+            let error = |message| ProgramError { line: token.line, column: token.column, index, message };
+            match token.text.parse::<isize>() {
+                Ok(value) => code.push(value),
+                Err(_) if token.text.is_empty() => return Err(error("Missing value".to_string())),
+                Err(_) => return Err(error(format!("Invalid value '{}'", token.text))),
+            }
+        }
+        let program = Program { code };
+        program.validate().map_err(|message| {
+            ProgramError { line: tokens[0].line, column: tokens[0].column, index: 0, message }
+        })?;
+        Ok(program)
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(fs::read_to_string(path)?.parse::<Program>()?)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match Computer::load(&self.code).decode_at(0) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Invalid entry instruction: {}", e)),
+        }
+    }
+
+    pub fn code(&self) -> &[isize] {
+        &self.code
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    pub fn computer(&self) -> Computer {
+        Computer::load(&self.code)
+    }
+}
+
+impl FromStr for Program {
+    type Err = ProgramError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Program::parse(s)
+    }
+}
+
+impl From<&Program> for Computer {
+    fn from(program: &Program) -> Self {
+        program.computer()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Program::parse(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn whitespace() {
+        let program = " 1, 0,0 ,0,\n99\n".parse::<Program>().unwrap();
+        assert_eq!(program.code(), &[1,0,0,0,99]);
+        assert_eq!(Program::parse("104,7,99,\n").unwrap().len(), 3);
+        assert_eq!(Program::parse("\r\n  99\r\n").unwrap().code(), &[99]);
+
+        let mut c = program.computer();
+        c.run().unwrap();
+        assert_eq!(c.peek(0), 2);
+    }
+
+    #[test]
+    fn bad_tokens() {
+        assert_eq!(error("1,0,0,0,99,x1"), "1:12: Invalid value 'x1' (token 5)");
+        assert_eq!(error("1,0,\n0,,0,99"), "2:3: Missing value (token 3)");
+        assert_eq!(error("104,7,\n  9 9"), "2:3: Invalid value '9 9' (token 2)");
+        assert_eq!(error("  \n"), "1:1: Empty program (token 0)");
+        assert_eq!(Program::parse("1,0,\n\n0,0,").unwrap().code(), &[1,0,0,0]);
+    }
+
+    #[test]
+    fn entry_instruction() {
+        assert_eq!(error("\n 42,0,0"), "2:2: Invalid entry instruction: Invalid opcode 42 at 0 (token 0)");
+        assert_eq!(error("30001,0,0,0"), "1:1: Invalid entry instruction: Invalid parameter mode 3 at 0 (token 0)");
+        assert_eq!(error("1,0"), "1:1: Invalid entry instruction: Program overrun at 0 (token 0)");
+        assert!(Program::parse("1,0,0,0").is_ok());
+    }
+}
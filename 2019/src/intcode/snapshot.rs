@@ -0,0 +1,274 @@
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use num::BigInt;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use super::{Arithmetic, Computer, MemoryModel, Program};
+
+const HEADER: &str = "intcode-snapshot 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub memory: Vec<isize>,
+    pub model: MemoryModel,
+    pub pages: Vec<(usize, Vec<isize>)>,
+    pub arithmetic: Arithmetic,
+    pub wide: Vec<(usize, BigInt)>,
+    pub ip: usize,
+    pub base: isize,
+    pub input: Vec<isize>,
+    pub output: Vec<isize>,
+}
+
+fn join(values: &[isize]) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    format!(" {}", values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+fn split(values: &str) -> Result<Vec<isize>> {
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(values.split(',').map(|v| v.trim().parse::<isize>()).collect::<Result<Vec<_>, _>>()?)
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "ip {}", self.ip)?;
+        writeln!(f, "base {}", self.base)?;
+        writeln!(f, "input{}", join(&self.input))?;
+        writeln!(f, "output{}", join(&self.output))?;
+        writeln!(f, "memory{}", join(&self.memory))?;
+        if self.model == MemoryModel::Paged {
+            writeln!(f, "model paged")?;
+        }
+        for (start, values) in &self.pages {
+            writeln!(f, "page {}{}", start, join(values))?;
+        }
+        if self.arithmetic != Arithmetic::Checked {
+            writeln!(f, "arithmetic {}", self.arithmetic)?;
+        }
+        for (addr, value) in &self.wide {
+            writeln!(f, "wide {} {}", addr, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.lines();
+        ensure!(lines.next().map(|l| l.trim()) == Some(HEADER), "Not an intcode snapshot");
+
+        let (mut ip, mut base, mut input, mut output, mut memory) = (None, None, None, None, None);
+        let (mut model, mut pages) = (MemoryModel::Dense, Vec::new());
+        let (mut arithmetic, mut wide) = (Arithmetic::Checked, Vec::new());
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let (key, value) = match line.find(' ') {
+                Some(pos) => (&line[..pos], line[pos + 1..].trim()),
+                None => (line.trim(), ""),
+            };
+            match key {
+                "ip" => ip = Some(value.parse::<usize>()?),
+                "base" => base = Some(value.parse::<isize>()?),
+                "input" => input = Some(split(value)?),
+                "output" => output = Some(split(value)?),
+                "memory" => memory = Some(split(value)?),
+                "model" => model = match value {
+                    "dense" => MemoryModel::Dense,
+                    "paged" => MemoryModel::Paged,
+                    _ => bail!("Unknown memory model `{}`", value),
+                },
+                "page" => {
+                    let (start, values) = value.split_at(value.find(' ').unwrap_or(value.len()));
+                    pages.push((start.parse::<usize>()?, split(values.trim())?));
+                },
+                "arithmetic" => arithmetic = value.parse::<Arithmetic>()?,
+                "wide" => {
+                    let mut parts = value.split_whitespace();
+                    let addr = parts.next().unwrap_or("").parse::<usize>()?;
+                    let value = parts.next().ok_or_else(|| anyhow!("Missing wide value"))?.parse::<BigInt>()?;
+                    wide.push((addr, value));
+                },
+                _ => bail!("Unknown snapshot field `{}`", key),
+            }
+        }
+
+        ensure!(pages.is_empty() || model == MemoryModel::Paged, "Pages require `model paged`");
+        let missing = |field| anyhow!("Missing snapshot field `{}`", field);
+        Ok(Snapshot {
+            ip: ip.ok_or_else(|| missing("ip"))?,
+            base: base.ok_or_else(|| missing("base"))?,
+            input: input.ok_or_else(|| missing("input"))?,
+            output: output.ok_or_else(|| missing("output"))?,
+            memory: memory.ok_or_else(|| missing("memory"))?,
+            model,
+            pages,
+            arithmetic,
+            wide,
+        })
+    }
+}
+
+impl Computer {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory.clone(),
+            model: self.memory_model(),
+            pages: self.pages.iter()
+                .flat_map(|pages| pages.iter())
+                .map(|(start, values)| {
+                    let len = values.iter().rposition(|v| *v != 0).map_or(0, |i| i + 1);
+                    (start, values[..len].to_vec())
+                })
+                .collect(),
+            arithmetic: self.arithmetic,
+            wide: self.wide.iter().map(|(addr, value)| (*addr, value.clone())).collect(),
+            ip: self.ip,
+            base: self.base,
+            input: self.input.iter().copied().collect(),
+            output: self.output.clone(),
+        }
+    }
+
+    pub fn restore(snapshot: &Snapshot) -> Self {
+        let mut computer = Computer::load_with(&snapshot.memory, snapshot.model);
+        if let Some(pages) = computer.pages.as_mut() {
+            for (start, values) in &snapshot.pages {
+                pages.insert(*start, values);
+            }
+        }
+        computer.arithmetic = snapshot.arithmetic;
+        computer.wide = snapshot.wide.iter().cloned().collect();
+        computer.ip = snapshot.ip;
+        computer.base = snapshot.base;
+        computer.input = snapshot.input.iter().copied().collect();
+        computer.output = snapshot.output.clone();
+        computer
+    }
+
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Ok(fs::write(path, self.snapshot().to_string())?)
+    }
+
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Computer::restore(&fs::read_to_string(path)?.parse::<Snapshot>()?))
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read_to_string(path)?;
+        if let Ok(snapshot) = data.parse::<Snapshot>() {
+            return Ok(Computer::restore(&snapshot));
+        }
+        Ok(data.parse::<Program>()?.computer())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::State;
+
+    fn counter() -> Computer {
+        Computer::load(&[3,12,1,12,13,13,4,13,1105,1,0,99,0,0])
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let mut c = counter();
+        c.push_input(5);
+        c.push_input(6);
+        c.push_input(7);
+        assert!(c.run_until_output().unwrap());
+
+        let snapshot = c.snapshot();
+        assert_eq!(snapshot.to_string(), concat!(
+            "intcode-snapshot 1\n",
+            "ip 8\n",
+            "base 0\n",
+            "input 6,7\n",
+            "output 5\n",
+            "memory 3,12,1,12,13,13,4,13,1105,1,0,99,5,5\n",
+        ));
+
+        let mut r = Computer::restore(&snapshot.to_string().parse::<Snapshot>().unwrap());
+        assert_eq!(r.snapshot(), snapshot);
+        assert_eq!(c.resume(), State::NeedsInput);
+        assert_eq!(r.resume(), State::NeedsInput);
+        assert_eq!(r.snapshot(), c.snapshot());
+        assert_eq!(r.output(), &[5, 11, 18]);
+    }
+
+    #[test]
+    fn snapshot_file() {
+        let path = std::env::temp_dir().join(format!("intcode-snapshot-{}.txt", std::process::id()));
+        let mut c = counter();
+        c.push_input(1);
+        assert_eq!(c.resume(), State::NeedsInput);
+        c.save_snapshot(&path).unwrap();
+
+        let mut r = Computer::load_snapshot(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        r.push_input(2);
+        assert_eq!(r.resume(), State::NeedsInput);
+        assert_eq!(r.output(), &[1, 3]);
+        assert!(Computer::load_snapshot(&path).is_err());
+    }
+
+    #[test]
+    fn paged_snapshot() {
+        let mut c = Computer::load_with(&[1101,7,0,4100,1101,0,0,2048,99], MemoryModel::Paged);
+        assert_eq!(c.resume(), State::Halted);
+
+        let snapshot = c.snapshot();
+        assert_eq!(snapshot.to_string(), concat!(
+            "intcode-snapshot 1\n",
+            "ip 8\n",
+            "base 0\n",
+            "input\n",
+            "output\n",
+            "memory 1101,7,0,4100,1101,0,0,2048,99\n",
+            "model paged\n",
+            "page 2048\n",
+            "page 4096 0,0,0,0,7\n",
+        ));
+
+        let r = Computer::restore(&snapshot.to_string().parse::<Snapshot>().unwrap());
+        assert_eq!(r.memory_model(), MemoryModel::Paged);
+        assert_eq!(r.snapshot(), snapshot);
+        assert_eq!(r.peek(4100), 7);
+        assert!("intcode-snapshot 1\nip 0\nbase 0\ninput\noutput\nmemory 99\nmodel huge".parse::<Snapshot>().is_err());
+        assert_eq!("intcode-snapshot 1\nip 0\nbase 0\ninput\noutput\nmemory 99\npage 2048 1".parse::<Snapshot>().unwrap_err().to_string(),
+            "Pages require `model paged`");
+    }
+
+    #[test]
+    fn wide_snapshot() {
+        let mut c = Computer::load(&[2,5,5,5,99,4294967296]);
+        c.set_arithmetic(Arithmetic::BigInt);
+        assert_eq!(c.resume(), State::Halted);
+
+        let snapshot = c.snapshot().to_string();
+        assert!(snapshot.ends_with("memory 2,5,5,5,99,0\narithmetic bigint\nwide 5 18446744073709551616\n"));
+        let r = Computer::restore(&snapshot.parse::<Snapshot>().unwrap());
+        assert_eq!(r.arithmetic(), Arithmetic::BigInt);
+        assert_eq!(r.peek_wide(5), c.peek_wide(5));
+        assert!("intcode-snapshot 1\nip 0\nbase 0\ninput\noutput\nmemory 99\nwide 1".parse::<Snapshot>().is_err());
+    }
+
+    #[test]
+    fn invalid_snapshot() {
+        let err = |s: &str| s.parse::<Snapshot>().unwrap_err().to_string();
+        assert_eq!(err("ip 1"), "Not an intcode snapshot");
+        assert_eq!(err("intcode-snapshot 1\nip 1\nbase 0\ninput\noutput\n"), "Missing snapshot field `memory`");
+        assert_eq!(err("intcode-snapshot 1\nregs 1\n"), "Unknown snapshot field `regs`");
+        assert!("intcode-snapshot 1\nip -1\nbase 0\ninput\noutput\nmemory 99".parse::<Snapshot>().is_err());
+        assert!("intcode-snapshot 1\nip 0\nbase 0\ninput\noutput\nmemory 99".parse::<Snapshot>().is_ok());
+    }
+}
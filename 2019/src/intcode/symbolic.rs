@@ -0,0 +1,395 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use super::{Budget, Computer, Fault, FaultKind, Opcode, State};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Const(isize),
+    Symbol(String),
+    Load(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Lt(Box<Expr>, Box<Expr>),
+    Eq(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Linear {
+    pub constant: isize,
+    pub terms: BTreeMap<String, isize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbolic {
+    computer: Computer,
+    cells: BTreeMap<usize, Expr>,
+    ip: usize,
+    base: isize,
+    input: VecDeque<Expr>,
+    output: Vec<Expr>,
+}
+
+impl Expr {
+    pub fn symbol(name: &str) -> Self {
+        Expr::Symbol(name.to_owned())
+    }
+
+    pub fn value(&self) -> Option<isize> {
+        match self {
+            Expr::Const(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn add(a: Expr, b: Expr) -> Self {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) if a.checked_add(b).is_some() => Expr::Const(a + b),
+            (Expr::Const(0), e) | (e, Expr::Const(0)) => e,
+            (a, b) => Expr::Add(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn mul(a: Expr, b: Expr) -> Self {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) if a.checked_mul(b).is_some() => Expr::Const(a * b),
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Expr::Const(0),
+            (Expr::Const(1), e) | (e, Expr::Const(1)) => e,
+            (a, b) => Expr::Mul(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn lt(a: Expr, b: Expr) -> Self {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const((a < b) as isize),
+            (a, b) => Expr::Lt(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn eq(a: Expr, b: Expr) -> Self {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const((a == b) as isize),
+            (a, b) => Expr::Eq(Box::new(a), Box::new(b)),
+        }
+    }
+
+    pub fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(v) => Some(Linear { constant: *v, terms: BTreeMap::new() }),
+            Expr::Symbol(name) => Some(Linear { constant: 0, terms: vec![(name.clone(), 1)].into_iter().collect() }),
+            Expr::Add(a, b) => a.linear()?.add(&b.linear()?),
+            Expr::Mul(a, b) => match (a.linear()?, b.linear()?) {
+                (a, b) if a.terms.is_empty() => b.scale(a.constant),
+                (a, b) if b.terms.is_empty() => a.scale(b.constant),
+                _ => None,
+            },
+            Expr::Load(_) | Expr::Lt(_, _) | Expr::Eq(_, _) => None,
+        }
+    }
+}
+
+impl From<isize> for Expr {
+    fn from(value: isize) -> Self {
+        Expr::Const(value)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Const(v) => write!(f, "{}", v),
+            Expr::Symbol(name) => write!(f, "{}", name),
+            Expr::Load(addr) => write!(f, "[{}]", addr),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Expr::Lt(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Eq(a, b) => write!(f, "({} == {})", a, b),
+        }
+    }
+}
+
+impl Linear {
+    fn add(mut self, other: &Linear) -> Option<Self> {
+        self.constant = self.constant.checked_add(other.constant)?;
+        for (name, coeff) in &other.terms {
+            let sum = self.terms.entry(name.clone()).or_insert(0);
+            *sum = sum.checked_add(*coeff)?;
+        }
+        self.terms.retain(|_, coeff| *coeff != 0);
+        Some(self)
+    }
+
+    fn scale(mut self, factor: isize) -> Option<Self> {
+        self.constant = self.constant.checked_mul(factor)?;
+        for coeff in self.terms.values_mut() {
+            *coeff = coeff.checked_mul(factor)?;
+        }
+        self.terms.retain(|_, coeff| *coeff != 0);
+        Some(self)
+    }
+
+    pub fn coefficient(&self, name: &str) -> isize {
+        self.terms.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn eval(&self, values: &BTreeMap<String, isize>) -> Option<isize> {
+        self.terms.iter().try_fold(self.constant, |sum, (name, coeff)| sum.checked_add(coeff.checked_mul(*values.get(name)?)?))
+    }
+
+    pub fn solve_for(&self, name: &str, target: isize, values: &BTreeMap<String, isize>) -> Option<isize> {
+        let coeff = self.coefficient(name);
+        let mut known = values.clone();
+        known.insert(name.to_owned(), 0);
+        let rest = target.checked_sub(self.eval(&known)?)?;
+        if coeff != 0 && rest % coeff == 0 {
+            Some(rest / coeff)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Linear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for (name, coeff) in &self.terms {
+            match (first, *coeff < 0) {
+                (true, false) => {},
+                (true, true) => write!(f, "-")?,
+                (false, false) => write!(f, " + ")?,
+                (false, true) => write!(f, " - ")?,
+            }
+            match coeff.abs() {
+                1 => write!(f, "{}", name)?,
+                c => write!(f, "{}*{}", c, name)?,
+            }
+            first = false;
+        }
+        match (first, self.constant) {
+            (true, c) => write!(f, "{}", c),
+            (false, 0) => Ok(()),
+            (false, c) if c < 0 => write!(f, " - {}", -c),
+            (false, c) => write!(f, " + {}", c),
+        }
+    }
+}
+
+impl Symbolic {
+    pub fn set_symbol(&mut self, addr: usize, name: &str) {
+        self.cells.insert(addr, Expr::symbol(name));
+    }
+
+    pub fn push_input(&mut self, value: Expr) {
+        self.input.push_back(value);
+    }
+
+    pub fn output(&self) -> &[Expr] {
+        &self.output
+    }
+
+    pub fn peek(&self, addr: usize) -> Expr {
+        match self.cells.get(&addr) {
+            Some(expr) => expr.clone(),
+            None => Expr::Const(self.computer.peek(addr)),
+        }
+    }
+
+    pub fn result(&self) -> Expr {
+        self.peek(0)
+    }
+
+    fn fault(&self, kind: FaultKind) -> Fault {
+        Fault { ip: self.ip, kind }
+    }
+
+    fn concrete(&self, expr: Expr, what: &str) -> Result<isize> {
+        expr.value().ok_or_else(|| anyhow!("Symbolic {} at {}: {}", what, self.ip, expr))
+    }
+
+    fn address(&self, idx: usize) -> Result<Option<Expr>> {
+        let mode = self.concrete(self.peek(self.ip), "opcode")? / 10isize.pow(idx as u32 + 1) % 10;
+        let value = self.peek(self.ip + idx);
+        match mode {
+            0 => Ok(Some(value)),
+            1 => Ok(None),
+            2 => Ok(Some(Expr::add(Expr::Const(self.base), value))),
+            mode => Err(self.fault(FaultKind::InvalidMode(mode)).into()),
+        }
+    }
+
+    fn param(&self, idx: usize) -> Result<Expr> {
+        match self.address(idx)? {
+            None => Ok(self.peek(self.ip + idx)),
+            Some(Expr::Const(addr)) if addr < 0 => Err(self.fault(FaultKind::NegativeAddress(addr)).into()),
+            Some(Expr::Const(addr)) => Ok(self.peek(addr as usize)),
+            Some(addr) => Ok(Expr::Load(Box::new(addr))),
+        }
+    }
+
+    fn store(&mut self, idx: usize, value: Expr) -> Result<()> {
+        let addr = match self.address(idx)? {
+            Some(addr) => self.concrete(addr, "write address")?,
+            None => return Err(self.fault(FaultKind::InvalidMode(1)).into()),
+        };
+        if addr < 0 {
+            return Err(self.fault(FaultKind::NegativeAddress(addr)).into());
+        }
+        self.cells.insert(addr as usize, value);
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<Option<State>> {
+        let code = self.concrete(self.peek(self.ip), "opcode")?;
+        let op = Opcode::try_from(code % 100).map_err(|_| self.fault(FaultKind::InvalidOpcode(code % 100)))?;
+        self.ip += match op {
+            Opcode::Add | Opcode::Mul | Opcode::Lt | Opcode::Eq => {
+                let (a, b) = (self.param(1)?, self.param(2)?);
+                let value = match op {
+                    Opcode::Add => Expr::add(a, b),
+                    Opcode::Mul => Expr::mul(a, b),
+                    Opcode::Lt => Expr::lt(a, b),
+                    _ => Expr::eq(a, b),
+                };
+                self.store(3, value)?;
+                4
+            },
+            Opcode::In => {
+                let value = match self.input.pop_front() {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                self.store(1, value)?;
+                2
+            },
+            Opcode::Out => {
+                let value = self.param(1)?;
+                self.output.push(value);
+                2
+            },
+            Opcode::Jt | Opcode::Jf => {
+                let cond = self.concrete(self.param(1)?, "jump condition")?;
+                if (cond != 0) == (op == Opcode::Jt) {
+                    self.ip = self.concrete(self.param(2)?, "jump target")? as usize;
+                    0
+                } else {
+                    3
+                }
+            },
+            Opcode::Arb => {
+                let offset = self.concrete(self.param(1)?, "base offset")?;
+                self.base = self.base.checked_add(offset).ok_or_else(|| self.fault(FaultKind::Overflow))?;
+                2
+            },
+            Opcode::Hlt => return Ok(Some(State::Halted)),
+        };
+        Ok(None)
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.computer.set_budget(budget);
+    }
+
+    pub fn run(&mut self) -> Result<State> {
+        loop {
+            if let Some(state) = self.computer.budget_interrupt() {
+                return Ok(state);
+            }
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+            self.computer.charge_budget();
+        }
+    }
+
+    pub fn run_to_halt(&mut self) -> Result<()> {
+        match self.run()? {
+            State::Halted => Ok(()),
+            State::BudgetExhausted => bail!("Budget exhausted"),
+            State::Stopped => bail!("Stopped"),
+            _ => bail!("Empty input"),
+        }
+    }
+}
+
+impl Computer {
+    pub fn symbolic(&self) -> Symbolic {
+        Symbolic {
+            computer: self.clone(),
+            cells: BTreeMap::new(),
+            ip: self.ip,
+            base: self.base,
+            input: self.input.iter().map(|v| Expr::Const(*v)).collect(),
+            output: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linear_result() {
+        let mut s = Computer::load(&[1,9,10,0,2,0,11,0,99,0,0,3]).symbolic();
+        s.set_symbol(9, "a");
+        s.set_symbol(10, "b");
+        s.run_to_halt().unwrap();
+        assert_eq!(s.result().to_string(), "((a + b) * 3)");
+        let linear = s.result().linear().unwrap();
+        assert_eq!(linear.to_string(), "3*a + 3*b");
+        assert_eq!(linear.coefficient("a"), 3);
+        let known = vec![("a".to_owned(), 4)].into_iter().collect();
+        assert_eq!(linear.solve_for("b", 30, &known), Some(6));
+        assert_eq!(linear.solve_for("b", 31, &known), None);
+        assert_eq!(linear.solve_for("b", 30, &BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn symbolic_addresses() {
+        let mut s = Computer::load(&[1,1,2,3,1,1,2,0,1002,0,-1,0,1001,0,7,0,99]).symbolic();
+        s.set_symbol(1, "noun");
+        s.set_symbol(2, "verb");
+        s.run_to_halt().unwrap();
+        assert_eq!(s.peek(3).to_string(), "([noun] + [verb])");
+        assert_eq!(s.peek(3).linear(), None);
+        assert_eq!(s.result().linear().unwrap().to_string(), "-noun - verb + 7");
+    }
+
+    #[test]
+    fn inputs_and_branches() {
+        let mut s = Computer::load(&[3,9,1001,9,5,10,4,10,99,0,0]).symbolic();
+        assert_eq!(s.run().unwrap(), State::NeedsInput);
+        s.push_input(Expr::symbol("x"));
+        s.run_to_halt().unwrap();
+        assert_eq!(s.output(), &[Expr::add(Expr::symbol("x"), Expr::Const(5))]);
+
+        let mut c = Computer::load(&[3,7,1005,7,6,99,99,0]);
+        c.push_input(0);
+        assert_eq!(c.symbolic().run().unwrap(), State::Halted);
+        let mut s = Computer::load(&[3,7,1005,7,6,99,99,0]).symbolic();
+        s.push_input(Expr::symbol("x"));
+        assert_eq!(s.run().unwrap_err().to_string(), "Symbolic jump condition at 2: x");
+    }
+
+    #[test]
+    fn overflow_and_budget() {
+        let big = Expr::mul(Expr::symbol("a"), Expr::Const(isize::MAX));
+        assert_eq!(Expr::add(big.clone(), Expr::symbol("a")).linear(), None);
+        assert_eq!(Expr::mul(big.clone(), Expr::Const(2)).linear(), None);
+        let linear = big.linear().unwrap();
+        let known = vec![("a".to_owned(), 2)].into_iter().collect();
+        assert_eq!(linear.eval(&known), None);
+        let shifted = Expr::add(big, Expr::Const(-5)).linear().unwrap();
+        assert_eq!(shifted.solve_for("a", isize::MAX, &BTreeMap::new()), None);
+
+        let mut s = Computer::load(&[1105,1,0]).symbolic();
+        s.set_budget(Budget::steps(100));
+        assert_eq!(s.run().unwrap(), State::BudgetExhausted);
+        assert_eq!(s.run_to_halt().unwrap_err().to_string(), "Budget exhausted");
+
+        let mut s = Computer::load(&[109,9223372036854775807,109,1,99]).symbolic();
+        let fault = s.run().unwrap_err().downcast::<Fault>().unwrap();
+        assert_eq!(fault, Fault { ip: 2, kind: FaultKind::Overflow });
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{anyhow, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+use super::{Computer, Fault, Opcode, Queues, State};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub ip: usize,
+    pub op: Opcode,
+    pub operands: Vec<isize>,
+    pub write: Option<(usize, isize)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub index: usize,
+    pub expected: Option<TraceStep>,
+    pub actual: Option<TraceStep>,
+}
+
+impl TraceStep {
+    pub fn input(&self) -> Option<isize> {
+        match (self.op, self.write) {
+            (Opcode::In, Some((_, value))) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn output(&self) -> Option<isize> {
+        match self.op {
+            Opcode::Out => self.operands.first().copied(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.ip, self.op)?;
+        if !self.operands.is_empty() {
+            write!(f, " {}", self.operands.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))?;
+        }
+        if let Some((addr, value)) = self.write {
+            write!(f, " -> {}={}", addr, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TraceStep {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (step, write) = match s.find(" -> ") {
+            Some(pos) => (&s[..pos], Some(&s[pos + 4..])),
+            None => (s, None),
+        };
+        let mut parts = step.split_whitespace();
+        let ip = parts.next().ok_or_else(|| anyhow!("Missing address"))?.parse::<usize>()?;
+        let op = parts.next().ok_or_else(|| anyhow!("Missing opcode"))?.parse::<Opcode>()?;
+        let operands = match parts.next() {
+            Some(list) => list.split(',').map(|v| v.parse::<isize>()).collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        let write = match write {
+            Some(w) => {
+                let mut kv = w.splitn(2, '=');
+                let addr = kv.next().unwrap().parse::<usize>()?;
+                let value = kv.next().ok_or_else(|| anyhow!("Missing written value"))?.parse::<isize>()?;
+                Some((addr, value))
+            },
+            None => None,
+        };
+        Ok(TraceStep { ip, op, operands, write })
+    }
+}
+
+impl Trace {
+    pub fn inputs(&self) -> impl Iterator<Item = isize> + '_ {
+        self.steps.iter().filter_map(|s| s.input())
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = isize> + '_ {
+        self.steps.iter().filter_map(|s| s.output())
+    }
+
+    pub fn divergence(&self, other: &Trace) -> Option<Divergence> {
+        let len = self.steps.len().max(other.steps.len());
+        (0..len)
+            .find(|i| self.steps.get(*i) != other.steps.get(*i))
+            .map(|index| Divergence {
+                index,
+                expected: self.steps.get(index).cloned(),
+                actual: other.steps.get(index).cloned(),
+            })
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Trace {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let steps = s.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| line.parse::<TraceStep>().map_err(|e| anyhow!("Line {}: {}", i + 1, e)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Trace { steps })
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |s: &Option<TraceStep>| s.as_ref().map(|s| s.to_string()).unwrap_or_else(|| "end of trace".to_owned());
+        write!(f, "Step {}: expected `{}`, found `{}`", self.index, show(&self.expected), show(&self.actual))
+    }
+}
+
+impl Computer {
+    pub fn start_trace(&mut self) {
+        self.trace = Some(Trace::default());
+    }
+
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    pub(super) fn trace_begin(&self) -> Result<TraceStep, Fault> {
+        let op = self.current_opcode()?;
+        let operands = (1..=op.params() as u32)
+            .filter(|idx| op.output_param() != Some(*idx))
+            .map(|idx| self.param(idx).copied())
+            .collect::<Result<Vec<_>, _>>()?;
+        let write = self.write_target()?.map(|addr| (addr as usize, 0));
+        Ok(TraceStep { ip: self.ip, op, operands, write })
+    }
+
+    pub(super) fn trace_end(&mut self, mut step: TraceStep) {
+        if let Some((addr, _)) = step.write {
+            step.write = Some((addr, self.peek(addr)));
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.steps.push(step);
+        }
+    }
+
+    pub fn replay(&self, trace: &Trace) -> Option<Divergence> {
+        let mut computer = self.clone();
+        computer.start_trace();
+        let mut queues = Queues { input: trace.inputs().collect(), output: Vec::new() };
+        for _ in 0..trace.steps.len() {
+            match computer.step(&mut queues) {
+                Ok(None) | Ok(Some(State::Output(_))) => {},
+                _ => break,
+            }
+        }
+        trace.divergence(computer.trace().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let mut c = Computer::load(&[3,9,8,9,10,9,4,9,99,-1,8]);
+        c.start_trace();
+        c.push_input(8);
+        assert!(c.run().is_ok());
+        assert_eq!(c.trace().unwrap().to_string(), concat!(
+            "0 in -> 9=8\n",
+            "2 eq 8,8 -> 9=1\n",
+            "6 out 1\n",
+            "8 hlt\n",
+        ));
+        assert_eq!(c.trace().unwrap().inputs().collect::<Vec<_>>(), &[8]);
+        assert_eq!(c.trace().unwrap().outputs().collect::<Vec<_>>(), &[1]);
+
+        let mut c = Computer::load(&[109,19,204,-19,99]);
+        c.start_trace();
+        assert!(c.run().is_ok());
+        assert_eq!(c.take_trace().unwrap().to_string(), "0 arb 19\n2 out 109\n4 hlt\n");
+        assert!(c.trace().is_none());
+    }
+
+    #[test]
+    fn parse_log() {
+        let log = "0 in -> 9=8\n2 eq 8,8 -> 9=1\n\n6 out 1\n8 hlt\n";
+        let trace = log.parse::<Trace>().unwrap();
+        assert_eq!(trace.steps[1], TraceStep { ip: 2, op: Opcode::Eq, operands: vec![8, 8], write: Some((9, 1)) });
+        assert_eq!(trace.to_string(), log.replace("\n\n", "\n"));
+        assert_eq!("1 out\n2 foo 3".parse::<Trace>().unwrap_err().to_string(), "Line 2: Invalid mnemonic");
+        assert!("1 add 1,x -> 3=2".parse::<Trace>().is_err());
+    }
+
+    #[test]
+    fn replay_divergence() {
+        let code = [3,9,8,9,10,9,4,9,99,-1,8];
+        let mut c = Computer::load(&code);
+        c.start_trace();
+        c.push_input(7);
+        assert!(c.run().is_ok());
+        let trace = c.take_trace().unwrap();
+        assert_eq!(Computer::load(&code).replay(&trace), None);
+
+        let patched = Computer::load(&[3,9,7,9,10,9,4,9,99,-1,8]);
+        let divergence = patched.replay(&trace).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.to_string(), "Step 1: expected `2 eq 7,8 -> 9=0`, found `2 lt 7,8 -> 9=1`");
+
+        let mut short = trace.clone();
+        short.steps.truncate(2);
+        assert_eq!(trace.divergence(&short).unwrap().to_string(), "Step 2: expected `6 out 0`, found `end of trace`");
+    }
+}
@@ -1,30 +1,47 @@
+use anyhow::{anyhow, Result};
+use aoc::intcode;
+
+#[path = "../../common/answer.rs"]
+mod answer;
+#[path = "../../common/answers.rs"]
+mod answers;
+#[path = "../../common/bench.rs"]
+mod bench;
+#[path = "../../common/cli.rs"]
+mod cli;
+#[path = "../../common/registry.rs"]
+mod registry;
 mod util;
-mod intcode;
+
+use answer::Answer;
+use registry::{Parsed, Solution};
 
 macro_rules! solution {
     ($day:ident => main) => {
-        mod $day;
+        solution!($day);
 
-        fn main() -> anyhow::Result<()> {
-            let (a, b) = $day::solution(&std::fs::read_to_string(format!("data/{}.txt", stringify!($day)))?)?;
-            println!("{}: {:} {:}", stringify!($day), a, b);
-            Ok(())
-        }
+        const MAIN: &str = stringify!($day);
     };
 
     ($day:ident) => {
         mod $day;
+    };
 
-        #[allow(dead_code)]
-        fn $day() -> anyhow::Result<()> {
-            let (a, b) = $day::solution(&std::fs::read_to_string(format!("data/{}.txt", stringify!($day)))?)?;
-            println!("{}: {:} {:}", stringify!($day), a, b);
-            Ok(())
+    (@registry $day:ident) => {
+        Solution {
+            day: registry::day_number(stringify!($day)),
+            name: stringify!($day),
+            parse: |input| Ok(Box::new($day::parse(input)?) as Parsed),
+            part1: |parsed| $day::part1(registry::downcast($day::parse, parsed)?).map(Answer::from),
+            part2: |parsed| $day::part2(registry::downcast($day::parse, parsed)?).map(Answer::from),
+            data: concat!("data/", stringify!($day), ".txt"),
         }
     };
 
     {$($day:ident $(=> $main:ident)?),+ $(,)?} => {
         $( solution!($day $(=> $main)?); )+
+
+        static SOLUTIONS: &[Solution] = &[$(solution!(@registry $day)),+];
     };
 }
 
@@ -46,4 +63,27 @@ solution! {
     day15,
     day16,
     day17 => main,
-}
\ No newline at end of file
+}
+
+fn main() -> Result<()> {
+    cli::main(|| {
+        let solution = Solution::find(MAIN).ok_or_else(|| anyhow!("No solution for {}", MAIN))?;
+        solution.run(&solution.input()?, None)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    const SLOW: &[&str] = &["day3", "day16"];
+
+    #[test]
+    fn accepted_answers() {
+        crate::answers::assert_accepted(|s| !SLOW.contains(&s.name));
+    }
+
+    #[test]
+    #[ignore = "slow without optimizations, run with cargo test --release -- --ignored"]
+    fn accepted_answers_slow() {
+        crate::answers::assert_accepted(|s| SLOW.contains(&s.name));
+    }
+}
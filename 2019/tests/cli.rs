@@ -0,0 +1,22 @@
+#[path = "../../common/tests/output.rs"]
+mod output;
+
+use output::{assert_csv, assert_json};
+
+const AOC: &str = env!("CARGO_BIN_EXE_aoc");
+
+#[test]
+fn bench_output() {
+    for day in &["13", "15"] {
+        assert_json(&output::run(AOC, &["bench", day, "--iterations", "2", "--format", "json"]));
+        assert_csv(&output::run(AOC, &["bench", day, "--iterations", "2", "--format", "csv"]));
+    }
+}
+
+#[test]
+fn run_output() {
+    for day in &["2", "8", "11", "13", "15"] {
+        assert_json(&output::run(AOC, &["run", day, "--format", "json"]));
+    }
+    assert_json(&output::run(AOC, &["run", "15", "--part", "2", "--format", "json"]));
+}
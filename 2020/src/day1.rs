@@ -4,35 +4,44 @@ use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use crate::util::parse_lines;
 
-/// Finds the first number combination in the line separated input that adds up to 2020.
+/// Parses the line separated expense report entries.
 /// 
 /// # Arguments
 /// 
 /// * `input`: Puzzle input string.
+///
+pub fn parse(input: &str) -> Result<Vec<usize>> {
+    Ok(parse_lines::<usize>(input)?)
+}
+
+/// Finds the first number combination in the entries that adds up to 2020.
+/// 
+/// # Arguments
+/// 
+/// * `entries`: Expense report entries.
 /// * `len`: Cardinality of combinations that we are looking for.
 ///          Specify 2 for pairs, 3 for triples, etc.
 ///
-fn find_combination(input: &str, len: usize) -> Result<Vec<usize>> {
-    parse_lines::<usize>(input)?
-    .into_iter()
+fn find_combination(entries: &[usize], len: usize) -> Result<Vec<usize>> {
+    entries.iter()
+    .copied()
     .combinations(len)
     .find(|combination| combination.iter().sum::<usize>() == 2020)
     .ok_or(anyhow!("No result"))
 }
 
-/// Finds the product of the first number combination in the line separated input
-/// that adds up to 2020.
+/// Finds the product of the first number combination in the entries that adds up to 2020.
 /// 
 /// Multiplies the outputs of [`find_combination`](self::find_combination) together to provide the result.
 /// 
 /// # Arguments
 /// 
-/// * `input`: Puzzle input string.
+/// * `entries`: Expense report entries.
 /// * `len`: Cardinality of combinations that we are looking for.
 ///          Specify 2 for pairs, 3 for triples, etc.
 /// 
-fn find_product(input: &str, len: usize) -> Result<usize> {
-    find_combination(input, len)
+fn find_product(entries: &[usize], len: usize) -> Result<usize> {
+    find_combination(entries, len)
     .map(|combination| combination.into_iter().product())
 }
 
@@ -40,20 +49,20 @@ fn find_product(input: &str, len: usize) -> Result<usize> {
 /// 
 /// # Arguments
 /// 
-/// * `input`: Puzzle input string.
+/// * `entries`: Expense report entries.
 ///
-pub fn part1(input: &str) -> Result<usize> {
-    find_product(input, 2)
+pub fn part1(entries: &[usize]) -> Result<usize> {
+    find_product(entries, 2)
 }
 
 /// Solution for part 2: product of three entries.
 /// 
 /// # Arguments
 /// 
-/// * `input`: Puzzle input string.
+/// * `entries`: Expense report entries.
 ///
-pub fn part2(input: &str) -> Result<usize> {
-    find_product(input, 3)
+pub fn part2(entries: &[usize]) -> Result<usize> {
+    find_product(entries, 3)
 }
 
 #[cfg(test)]
@@ -72,15 +81,17 @@ mod test {
 
     #[test]
     fn combination() -> Result<()> {
-        assert_eq!(find_combination(INPUT, 2)?, [1721, 299]);
-        assert_eq!(find_combination(INPUT, 3)?, [979, 366, 675]);
+        let entries = parse(INPUT)?;
+        assert_eq!(find_combination(&entries, 2)?, [1721, 299]);
+        assert_eq!(find_combination(&entries, 3)?, [979, 366, 675]);
         Ok(())
     }
 
     #[test]
     fn product() -> Result<()> {
-        assert_eq!(find_product(INPUT, 2)?, 514579);
-        assert_eq!(find_product(INPUT, 3)?, 241861950);
+        let entries = parse(INPUT)?;
+        assert_eq!(find_product(&entries, 2)?, 514579);
+        assert_eq!(find_product(&entries, 3)?, 241861950);
         Ok(())
     }
 }
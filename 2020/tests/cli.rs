@@ -0,0 +1,18 @@
+#[path = "../../common/tests/output.rs"]
+mod output;
+
+use output::{assert_csv, assert_json};
+
+const AOC: &str = env!("CARGO_BIN_EXE_aoc2020");
+
+#[test]
+fn run_output() {
+    assert_json(&output::run(AOC, &["run", "all", "--format", "json"]));
+    assert_json(&output::run(AOC, &["run", "1", "--part", "2", "--format", "json"]));
+}
+
+#[test]
+fn bench_output() {
+    assert_json(&output::run(AOC, &["bench", "all", "--iterations", "2", "--format", "json"]));
+    assert_csv(&output::run(AOC, &["bench", "all", "--iterations", "2", "--format", "csv"]));
+}
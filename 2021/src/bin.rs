@@ -1,3 +1,9 @@
-pub fn main() -> anyhow::Result<()> {
-    aoc::MAIN()
-}
\ No newline at end of file
+use anyhow::Result;
+use aoc::{answers, bench, registry, SOLUTIONS};
+
+#[path = "../../common/cli.rs"]
+mod cli;
+
+fn main() -> Result<()> {
+    cli::main(aoc::MAIN)
+}
@@ -11,12 +11,15 @@ fn count_increase(data: impl Iterator<Item = usize>) -> usize {
     .1
 }
 
-pub fn part1(input: &str) -> Result<usize> {
-    Ok(count_increase(parse_lines::<usize>(input)?.into_iter()))
+pub fn parse(input: &str) -> Result<Vec<usize>> {
+    Ok(parse_lines::<usize>(input)?)
 }
 
-pub fn part2(input: &str) -> Result<usize> {
-    let data = parse_lines::<usize>(input)?;
+pub fn part1(data: &[usize]) -> Result<usize> {
+    Ok(count_increase(data.iter().copied()))
+}
+
+pub fn part2(data: &[usize]) -> Result<usize> {
     count_increase(
         izip!(&data[0..data.len()-2], &data[1..data.len()-1], &data[2..data.len()])
         .map(|(a, b, c)| a + b + c)
@@ -43,8 +46,9 @@ mod test {
 
     #[test]
     fn count() -> Result<()> {
-        assert_eq!(part1(INPUT)?, 7);
-        assert_eq!(part2(INPUT)?, 5);
+        let data = parse(INPUT)?;
+        assert_eq!(part1(&data)?, 7);
+        assert_eq!(part2(&data)?, 5);
         Ok(())
     }
 }
@@ -6,7 +6,7 @@ use anyhow::{anyhow, Result, Error};
 use crate::util::parse_lines;
 
 #[derive(Debug)]
-enum Command {
+pub enum Command {
     Up(usize),
     Down(usize),
     Forward(usize),
@@ -28,12 +28,16 @@ impl FromStr for Command {
     }
 }
 
-pub fn part1(input: &str) -> Result<usize> {
-    let (depth, horiz) = parse_lines::<Command>(input)?
-    .into_iter()
+pub fn parse(input: &str) -> Result<Vec<Command>> {
+    parse_lines::<Command>(input)
+}
+
+pub fn part1(commands: &[Command]) -> Result<usize> {
+    let (depth, horiz) = commands
+    .iter()
     .fold((0usize, 0usize),
         |(depth, horiz), cmd|
-            match cmd {
+            match *cmd {
                 Command::Up(arg) => (depth - arg, horiz),
                 Command::Down(arg) => (depth + arg, horiz),
                 Command::Forward(arg) => (depth, horiz + arg),
@@ -42,12 +46,12 @@ pub fn part1(input: &str) -> Result<usize> {
     Ok(depth * horiz)
 }
 
-pub fn part2(input: &str) -> Result<usize> {
-    let (_, depth, horiz) = parse_lines::<Command>(input)?
-    .into_iter()
+pub fn part2(commands: &[Command]) -> Result<usize> {
+    let (_, depth, horiz) = commands
+    .iter()
     .fold((0usize, 0usize, 0usize),
         |(aim, depth, horiz), cmd|
-            match cmd {
+            match *cmd {
                 Command::Up(arg) => (aim - arg, depth, horiz),
                 Command::Down(arg) => (aim + arg, depth, horiz),
                 Command::Forward(arg) => (aim, depth + (arg * aim), horiz + arg),
@@ -72,13 +76,13 @@ mod test {
 
     #[test]
     fn solution1() -> Result<()> {
-        assert_eq!(part1(INPUT)?, 150);
+        assert_eq!(part1(&parse(INPUT)?)?, 150);
         Ok(())
     }
 
     #[test]
     fn solution2() -> Result<()> {
-        assert_eq!(part2(INPUT)?, 900);
+        assert_eq!(part2(&parse(INPUT)?)?, 900);
         Ok(())
     }
 }
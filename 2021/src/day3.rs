@@ -26,13 +26,16 @@ fn rates(ones: &[usize], lines: usize) -> (usize, usize) {
     (gamma, epsilon)
 }
 
-pub fn part1(input: &str) -> Result<usize> {
-    let lines = parse_lines::<String>(input)?;
-    let (gamma, epsilon) = rates(&count_ones(&lines), lines.len());
+pub fn parse(input: &str) -> Result<Vec<String>> {
+    Ok(parse_lines::<String>(input)?)
+}
+
+pub fn part1(lines: &[String]) -> Result<usize> {
+    let (gamma, epsilon) = rates(&count_ones(lines), lines.len());
     Ok(gamma * epsilon)
 }
 
-pub fn part2(input: &str) -> Result<usize> {
+pub fn part2(input: &[String]) -> Result<usize> {
     Ok(0)
 }
 
@@ -58,11 +61,11 @@ mod test {
 
     #[test]
     fn solution1() -> Result<()> {
-        let lines = parse_lines::<String>(INPUT)?;
+        let lines = parse(INPUT)?;
         let ones = count_ones(&lines);
         assert_eq!(ones, &[7, 5, 8, 7, 5]);
         assert_eq!(rates(&ones, lines.len()), (22, 9));
-        assert_eq!(part1(INPUT)?, 198);
+        assert_eq!(part1(&lines)?, 198);
         Ok(())
     }
 }
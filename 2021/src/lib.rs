@@ -10,22 +10,36 @@
 //! }
 //! ```
 
+#[path = "../../common/answer.rs"]
+pub mod answer;
+#[path = "../../common/answers.rs"]
+pub mod answers;
+#[path = "../../common/bench.rs"]
+pub mod bench;
+#[path = "../../common/registry.rs"]
+pub mod registry;
 pub mod util;
 
 /// Generates wrapper function for reading input and printing output for each day's solution.
 /// 
-/// Each solution must reside in its on module and provide two functions `part1` and `part2`,
-/// both with the same signature:
+/// Each solution must reside in its on module and provide a function `parse`, and two functions
+/// `part1` and `part2` solving the puzzle from its result, both with the same signature:
 /// 
+/// ```ignore
+/// fn parse(input: &str) -> anyhow::Result<Input> { /* ... */ }
+/// fn part<T: Into<aoc::answer::Answer>>(input: &Input) -> anyhow::Result<T> { /* ... */ }
 /// ```
-/// fn part<T: Debug>(input: &str) -> anyhow::Result<T> { /* ... */ }
-/// ```
+/// 
+/// The input is parsed once and shared by both parts, so that the parsing can be timed on its own.
+/// 
+/// The declared solutions are also collected into [`SOLUTIONS`], in declaration order,
+/// so that runners can enumerate them.
 /// 
 /// # Examples
 /// 
 /// Declare 3 solutions, of which the second one will run:
 /// 
-/// ```
+/// ```ignore
 /// solution! {
 ///     day1,
 ///     day2 => main,
@@ -63,16 +77,27 @@ macro_rules! solution {
         #[doc = $doc]
         #[allow(dead_code)]
         pub fn $day() -> anyhow::Result<()> {
-            let input = std::fs::read_to_string(format!("data/{}.txt", stringify!($day)))?;
-            let part1 = $day::part1(&input)?;
-            let part2 = $day::part2(&input)?;
-            println!("{}: {:?} {:?}", stringify!($day), part1, part2);
-            Ok(())
+            let solution = solution!(@registry $day);
+            solution.run(&solution.input()?, None)
+        }
+    };
+
+    (@registry $day:ident) => {
+        $crate::registry::Solution {
+            day: $crate::registry::day_number(stringify!($day)),
+            name: stringify!($day),
+            parse: |input| Ok(Box::new($day::parse(input)?) as $crate::registry::Parsed),
+            part1: |parsed| $day::part1($crate::registry::downcast($day::parse, parsed)?).map($crate::answer::Answer::from),
+            part2: |parsed| $day::part2($crate::registry::downcast($day::parse, parsed)?).map($crate::answer::Answer::from),
+            data: concat!("data/", stringify!($day), ".txt"),
         }
     };
 
     {$($day:ident $(=> $main:ident)?),+ $(,)?} => {
         $( solution!($day $(=> $main)?); )+
+
+        /// All solutions declared with `solution!`, in declaration order.
+        pub static SOLUTIONS: &[$crate::registry::Solution] = &[$(solution!(@registry $day)),+];
     };
 }
 
@@ -80,4 +105,12 @@ solution! {
     day1,
     day2,
     day3 => main,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn accepted_answers() {
+        crate::answers::assert_accepted(|_| true);
+    }
+}
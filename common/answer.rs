@@ -0,0 +1,150 @@
+//! Common result type of the solutions.
+
+use std::fmt;
+
+/// Answer to one part of a puzzle.
+/// 
+/// Answers compare equal when they are displayed the same way, so a number returned as a string
+/// matches the same number returned as an integer.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    /// Numeric answer.
+    Integer(i128),
+    /// Single line of text.
+    Text(String),
+    /// Rendered picture, e.g. letters drawn as ASCII art, without trailing whitespace.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    /// Creates a grid answer from rendered rows.
+    /// 
+    /// Trailing whitespace of the rows and blank rows around the picture are removed.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `rows`: Rows of the picture
+    pub fn grid<I, S>(rows: I) -> Self
+        where I: IntoIterator<Item = S>, S: AsRef<str>
+    {
+        let mut rows = rows.into_iter().map(|r| r.as_ref().trim_end().to_string()).collect::<Vec<_>>();
+        while rows.last().is_some_and(String::is_empty) {
+            rows.pop();
+        }
+        let blank = rows.iter().take_while(|r| r.is_empty()).count();
+        Answer::Grid(rows.split_off(blank))
+    }
+
+    /// Returns the answer as a JSON value: a number, a string or an array of rows.
+    pub fn to_json(&self) -> String {
+        fn string(s: &str) -> String {
+            let mut json = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => json.push_str("\\\""),
+                    '\\' => json.push_str("\\\\"),
+                    '\n' => json.push_str("\\n"),
+                    c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => json.push(c),
+                }
+            }
+            json.push('"');
+            json
+        }
+
+        match self {
+            Answer::Integer(n) => n.to_string(),
+            Answer::Text(s) => string(s),
+            Answer::Grid(rows) => format!("[{}]", rows.iter().map(|r| string(r)).collect::<Vec<_>>().join(", ")),
+        }
+    }
+
+    /// Formats the answers of a solution on one line, with pictures starting on a new line.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name`: Name of the solution
+    /// * `answers`: Answers to print
+    pub fn line(name: &str, answers: &[Answer]) -> String {
+        let mut line = format!("{}:", name);
+        for answer in answers {
+            line.push(if matches!(answer, Answer::Grid(_)) { '\n' } else { ' ' });
+            line.push_str(&answer.to_string());
+        }
+        line
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for Answer {}
+
+macro_rules! from_integer {
+    ($($t:ty),+) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Integer(n as i128)
+                }
+            }
+        )+
+    };
+}
+
+from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl From<&str> for Answer {
+    /// Recognizes integers and multi-line pictures in text, so that answers can be read back.
+    fn from(s: &str) -> Self {
+        match s.trim().parse::<i128>() {
+            Ok(n) if n.to_string() == s.trim() => Answer::Integer(n),
+            _ if s.contains('\n') => Answer::grid(s.lines()),
+            _ => Answer::Text(s.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::from(s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Answer::from(42usize).to_string(), "42");
+        assert_eq!(Answer::from("-7"), Answer::from(-7isize));
+        assert!(matches!(Answer::from("0123"), Answer::Text(_)));
+        assert!(matches!(Answer::from("52611030".to_string()), Answer::Integer(52611030)));
+        assert_eq!(Answer::from("\n ## \n#  #\n\n"), Answer::Grid(vec![" ##".to_string(), "#  #".to_string()]));
+        assert_ne!(Answer::from("abc"), Answer::from("abd"));
+    }
+
+    #[test]
+    fn formatting() {
+        let grid = Answer::grid(["#.", ".#  "]);
+        assert_eq!(grid.to_string(), "#.\n.#");
+        assert_eq!(grid.to_json(), r##"["#.", ".#"]"##);
+        assert_eq!(Answer::from("a \"b\"").to_json(), r#""a \"b\"""#);
+        assert_eq!(Answer::from(5u8).to_json(), "5");
+        assert_eq!(Answer::line("day8", &[Answer::from(1u8), grid]), "day8: 1\n#.\n.#");
+    }
+}
@@ -0,0 +1,261 @@
+//! Accepted puzzle answers and verification of the solutions against them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use anyhow::{anyhow, bail, Error, Result};
+use crate::answer::Answer;
+use crate::registry::Solution;
+
+/// Accepted answers, read from a small subset of TOML with one table per solution:
+/// 
+/// ```toml
+/// [day1]
+/// part1 = 1713
+/// part2 = "text"
+/// ```
+/// 
+/// Values can be integers, basic `"strings"`, literal `'strings'` and multi-line literal
+/// `'''strings'''` for answers drawn as ASCII art.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers {
+    tables: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+fn basic_string(text: &str) -> Result<String> {
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().trim().is_empty() => return Ok(value),
+            '"' => bail!("Unexpected text after string"),
+            '\\' => value.push(match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('"') => '"',
+                Some('\\') => '\\',
+                c => bail!("Unsupported escape {:?}", c),
+            }),
+            c => value.push(c),
+        }
+    }
+    bail!("Unterminated string")
+}
+
+impl FromStr for Answers {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut answers = Answers::default();
+        let mut table = None;
+        let mut lines = s.lines().enumerate();
+        while let Some((idx, line)) = lines.next() {
+            let error = |e: Error| anyhow!("line {}: {}", idx + 1, e);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                table = Some(answers.tables.entry(name.trim().to_string()).or_default());
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| error(anyhow!("Expected key = value")))?;
+            let (key, value) = (key.trim(), value.trim());
+            let value = if let Some(rest) = value.strip_prefix("'''") {
+                let mut text = rest.to_string();
+                while !text.ends_with("'''") {
+                    let (_, line) = lines.next().ok_or_else(|| error(anyhow!("Unterminated multi-line string")))?;
+                    text.push('\n');
+                    text.push_str(line);
+                }
+                text.truncate(text.len() - 3);
+                text.strip_prefix('\n').map(str::to_string).unwrap_or(text)
+            } else if let Some(rest) = value.strip_prefix('"') {
+                basic_string(rest).map_err(error)?
+            } else if let Some(rest) = value.strip_prefix('\'') {
+                rest.strip_suffix('\'').ok_or_else(|| error(anyhow!("Unterminated string")))?.to_string()
+            } else if value.parse::<i128>().is_ok() {
+                value.to_string()
+            } else {
+                return Err(error(anyhow!("Unsupported value {}", value)));
+            };
+            table.as_mut().ok_or_else(|| error(anyhow!("Key {} outside of a table", key)))?
+                .insert(key.to_string(), value);
+        }
+        Ok(answers)
+    }
+}
+
+impl Answers {
+    /// Default location of the answers file, relative to the crate root.
+    pub const PATH: &'static str = "data/answers.toml";
+
+    /// Reads answers from a file.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path`: Path of the answers file
+    /// 
+    /// # Errors
+    /// 
+    /// I/O errors and syntax errors, the latter with the line number.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let error = |e: Error| anyhow!("{}: {}", path.display(), e);
+        fs::read_to_string(path).map_err(|e| error(e.into()))?.parse().map_err(error)
+    }
+
+    /// Returns the accepted answer of a part, if known.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name`: Name of the solution, e.g. `"day3"`
+    /// * `part`: Part number, `1` or `2`
+    pub fn get(&self, name: &str, part: u8) -> Option<&str> {
+        self.tables.get(name)?.get(&format!("part{}", part)).map(String::as_str)
+    }
+}
+
+/// Outcome of checking one part of a solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// The answer matches the accepted one.
+    Correct,
+    /// The answer differs from the accepted one.
+    Wrong { expected: Answer, actual: Answer },
+    /// There is no accepted answer to compare the result with.
+    MissingAnswer(Answer),
+    /// The puzzle input file does not exist.
+    MissingInput,
+    /// The solution returned an error.
+    Failed(String),
+}
+
+/// Result of verifying one part of a solution.
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// Verified solution.
+    pub solution: &'static Solution,
+    /// Part number.
+    pub part: u8,
+    /// Outcome of the check.
+    pub status: Status,
+}
+
+impl Check {
+    /// Returns `true` for wrong answers and failed solutions.
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, Status::Wrong { .. } | Status::Failed(_))
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} part{}: ", self.solution.name, self.part)?;
+        match &self.status {
+            Status::Correct => write!(f, "ok"),
+            Status::Wrong { expected, actual } => write!(f, "WRONG, expected {} but got {}", expected, actual),
+            Status::MissingAnswer(actual) => write!(f, "no accepted answer, got {}", actual),
+            Status::MissingInput => write!(f, "missing input {}", self.solution.data),
+            Status::Failed(e) => write!(f, "FAILED: {}", e),
+        }
+    }
+}
+
+/// Runs both parts of a solution on its puzzle input and compares the results with the accepted answers.
+/// 
+/// # Arguments
+/// 
+/// * `solution`: Solution to verify
+/// * `answers`: Accepted answers
+pub fn verify(solution: &'static Solution, answers: &Answers) -> Vec<Check> {
+    let parsed = match fs::read_to_string(solution.data) {
+        Ok(input) => (solution.parse)(&input).map_err(|e| Status::Failed(e.to_string())),
+        Err(_) => Err(Status::MissingInput),
+    };
+    [1, 2].iter().map(|&part| {
+        let status = match (&parsed, answers.get(solution.name, part)) {
+            (Err(status), _) => status.clone(),
+            (Ok(parsed), expected) => match (solution.part(part).unwrap())(parsed.as_ref()) {
+                Err(e) => Status::Failed(e.to_string()),
+                Ok(actual) => match expected.map(Answer::from) {
+                    None => Status::MissingAnswer(actual),
+                    Some(expected) if expected == actual => Status::Correct,
+                    Some(expected) => Status::Wrong { expected, actual },
+                },
+            },
+        };
+        Check { solution, part, status }
+    }).collect()
+}
+
+/// Verifies the registered solutions against the accepted answers in [`Answers::PATH`].
+/// 
+/// Shared by the `accepted_answers` tests of the solution crates.
+/// 
+/// # Arguments
+/// 
+/// * `filter`: Selects the solutions to verify
+/// 
+/// # Panics
+/// 
+/// If any of the checks failed.
+#[cfg(test)]
+pub fn assert_accepted(filter: fn(&Solution) -> bool) {
+    let answers = Answers::load(Answers::PATH).unwrap();
+    let failures = crate::SOLUTIONS.iter()
+        .filter(|s| filter(s))
+        .flat_map(|s| verify(s, &answers))
+        .filter(Check::is_failure)
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse() {
+        let answers = indoc!(r#"
+            # Accepted answers
+            [day1]
+            part1 = 1713
+            part2 = "a \"b\"\n"
+
+            [day8]
+            part1 = 'x'
+            part2 = '''
+              ##
+            ##  ##
+            '''
+        "#).parse::<Answers>().unwrap();
+        assert_eq!(answers.get("day1", 1), Some("1713"));
+        assert_eq!(answers.get("day1", 2), Some("a \"b\"\n"));
+        assert_eq!(answers.get("day8", 1), Some("x"));
+        assert_eq!(answers.get("day8", 2), Some("  ##\n##  ##\n"));
+        assert_eq!(answers.get("day2", 1), None);
+
+        assert_eq!("[day1]\npart1 = abc".parse::<Answers>().unwrap_err().to_string(), "line 2: Unsupported value abc");
+        assert_eq!("part1 = 1".parse::<Answers>().unwrap_err().to_string(), "line 1: Key part1 outside of a table");
+        assert!("[day1]\npart1 = '''\nx".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn missing_answers() {
+        let day1 = &crate::SOLUTIONS[0];
+        let checks = verify(day1, &Answers::default());
+        assert_eq!(checks.iter().map(|c| c.part).collect::<Vec<_>>(), [1, 2]);
+        assert!(checks.iter().all(|c| matches!(c.status, Status::MissingAnswer(_)) && !c.is_failure()));
+        assert!(checks[0].to_string().starts_with("day1 part1: no accepted answer, got "));
+
+        let answers = "[day1]\npart1 = 0".parse::<Answers>().unwrap();
+        let checks = verify(day1, &answers);
+        assert!(matches!(checks[0].status, Status::Wrong { .. }) && checks[0].is_failure());
+        assert!(matches!(checks[1].status, Status::MissingAnswer(_)));
+    }
+}
@@ -0,0 +1,167 @@
+//! Timing of solutions over repeated runs, with text, JSON and CSV reports.
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use anyhow::{bail, Error, Result};
+
+/// Report format of the benchmark results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned table for reading in a terminal.
+    Text,
+    /// Array of JSON objects, one per timed phase.
+    Json,
+    /// Comma separated values with a header line.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => bail!("Unknown format {}, expected text, json or csv", s),
+        }
+    }
+}
+
+/// Durations of repeated runs of one phase of a solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    /// Name of the solution, e.g. `"day3"`.
+    pub name: &'static str,
+    /// Timed phase, e.g. `"part1"`.
+    pub phase: &'static str,
+    /// Duration of each run, sorted in ascending order.
+    pub samples: Vec<Duration>,
+}
+
+impl Timing {
+    /// Shortest run.
+    pub fn min(&self) -> Duration {
+        self.samples.first().copied().unwrap_or_default()
+    }
+
+    /// Median of the runs, the mean of the two middle ones for an even count.
+    pub fn median(&self) -> Duration {
+        let len = self.samples.len();
+        match len {
+            0 => Duration::default(),
+            _ if len % 2 == 1 => self.samples[len / 2],
+            _ => (self.samples[len / 2 - 1] + self.samples[len / 2]) / 2,
+        }
+    }
+
+    /// Longest run.
+    pub fn max(&self) -> Duration {
+        self.samples.last().copied().unwrap_or_default()
+    }
+}
+
+/// Runs a phase of a solution repeatedly and collects the durations.
+/// 
+/// # Arguments
+/// 
+/// * `name`: Name of the solution
+/// * `phase`: Name of the timed phase
+/// * `iterations`: Number of runs, at least one
+/// * `f`: The phase to run, its result is kept from being optimized away
+/// 
+/// # Errors
+/// 
+/// The first error returned by `f`.
+pub fn measure<T, F>(name: &'static str, phase: &'static str, iterations: usize, mut f: F) -> Result<Timing>
+    where F: FnMut() -> Result<T>
+{
+    let mut samples = Vec::with_capacity(iterations.max(1));
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        black_box(f()?);
+        samples.push(start.elapsed());
+    }
+    samples.sort_unstable();
+    Ok(Timing { name, phase, samples })
+}
+
+fn text(duration: Duration) -> String {
+    match duration.as_nanos() {
+        n if n < 1_000 => format!("{}ns", n),
+        n if n < 1_000_000 => format!("{:.1}µs", n as f64 / 1e3),
+        n if n < 1_000_000_000 => format!("{:.1}ms", n as f64 / 1e6),
+        n => format!("{:.2}s", n as f64 / 1e9),
+    }
+}
+
+/// Formats benchmark results.
+/// 
+/// Durations are given in nanoseconds in JSON and CSV reports.
+/// 
+/// # Arguments
+/// 
+/// * `timings`: Results of [`measure`]
+/// * `format`: Report format
+pub fn report(timings: &[Timing], format: Format) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Text => {
+            writeln!(out, "{:<8} {:<6} {:>6} {:>10} {:>10} {:>10}", "day", "phase", "runs", "min", "median", "max").unwrap();
+            for t in timings {
+                writeln!(out, "{:<8} {:<6} {:>6} {:>10} {:>10} {:>10}",
+                    t.name, t.phase, t.samples.len(), text(t.min()), text(t.median()), text(t.max())).unwrap();
+            }
+        },
+        Format::Json => {
+            let items = timings.iter()
+                .map(|t| format!("  {{\"day\": \"{}\", \"phase\": \"{}\", \"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+                    t.name, t.phase, t.samples.len(), t.min().as_nanos(), t.median().as_nanos(), t.max().as_nanos()))
+                .collect::<Vec<_>>();
+            writeln!(out, "[\n{}\n]", items.join(",\n")).unwrap();
+        },
+        Format::Csv => {
+            writeln!(out, "day,phase,runs,min_ns,median_ns,max_ns").unwrap();
+            for t in timings {
+                writeln!(out, "{},{},{},{},{},{}",
+                    t.name, t.phase, t.samples.len(), t.min().as_nanos(), t.median().as_nanos(), t.max().as_nanos()).unwrap();
+            }
+        },
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timing(samples: &[u64]) -> Timing {
+        Timing { name: "day1", phase: "part1", samples: samples.iter().map(|n| Duration::from_nanos(*n)).collect() }
+    }
+
+    #[test]
+    fn statistics() {
+        let t = timing(&[10, 20, 40]);
+        assert_eq!((t.min(), t.median(), t.max()), (Duration::from_nanos(10), Duration::from_nanos(20), Duration::from_nanos(40)));
+        assert_eq!(timing(&[10, 20, 40, 50]).median(), Duration::from_nanos(30));
+
+        let mut runs = 0;
+        let t = measure("day1", "part2", 5, || { runs += 1; Ok(runs) }).unwrap();
+        assert_eq!((runs, t.samples.len()), (5, 5));
+        assert!(t.samples.windows(2).all(|w| w[0] <= w[1]));
+        assert!(measure("day1", "part2", 5, || -> Result<()> { bail!("Failed") }).is_err());
+    }
+
+    #[test]
+    fn reports() {
+        let timings = [timing(&[1500, 2_000_000]), Timing { phase: "part2", ..timing(&[3]) }];
+        assert_eq!(report(&timings, Format::Csv), "day,phase,runs,min_ns,median_ns,max_ns\nday1,part1,2,1500,1000750,2000000\nday1,part2,1,3,3,3\n");
+        assert_eq!(report(&timings[1..], Format::Json),
+            "[\n  {\"day\": \"day1\", \"phase\": \"part2\", \"runs\": 1, \"min_ns\": 3, \"median_ns\": 3, \"max_ns\": 3}\n]\n");
+        assert!(report(&timings, Format::Text).lines().nth(1).unwrap().ends_with("1.5µs      1.0ms      2.0ms"));
+        assert_eq!("csv".parse::<Format>().unwrap(), Format::Csv);
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
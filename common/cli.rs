@@ -0,0 +1,169 @@
+//! Command line driver of the solution runner binaries.
+//! 
+//! The including crate provides the [`answers`](crate::answers), [`bench`](crate::bench) and
+//! [`registry`](crate::registry) modules and the [`SOLUTIONS`](crate::SOLUTIONS) table at its root.
+
+use std::fs;
+use std::io::{self, Read};
+use anyhow::{anyhow, bail, Result};
+use crate::SOLUTIONS;
+use crate::answers::{self, Answers, Status};
+use crate::bench::{self, Format};
+use crate::registry::Solution;
+
+const USAGE: &str = concat!("Usage: ", env!("CARGO_BIN_NAME"), " [list | run <day|all> [--part <1|2>] [--input <path|->] [--format <text|json>]
+        | bench <day|all> [--part <1|2>] [--input <path|->] [--iterations <n>] [--format <text|json|csv>]
+        | verify [day|all] [--answers <path>]]");
+
+fn find_day(arg: &str) -> Result<&'static Solution> {
+    Solution::find(arg).ok_or_else(|| {
+        let days = SOLUTIONS.iter().map(|s| s.name).collect::<Vec<_>>();
+        anyhow!("No solution for {}, available: {}", arg, days.join(", "))
+    })
+}
+
+fn read_input(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        Ok(fs::read_to_string(path)?)
+    }
+}
+
+struct Options<'a> {
+    days: Vec<&'static Solution>,
+    part: Option<u8>,
+    input: Option<&'a str>,
+    answers: Option<&'a str>,
+    iterations: usize,
+    format: Format,
+}
+
+fn options(args: &[String]) -> Result<Options<'_>> {
+    let mut day = None;
+    let mut options = Options { days: Vec::new(), part: None, input: None, answers: None, iterations: 10, format: Format::Text };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!(USAGE));
+        match arg.as_str() {
+            "--part" | "-p" => options.part = Some(value()?.parse::<u8>().map_err(|_| anyhow!(USAGE))?),
+            "--input" | "-i" => options.input = Some(value()?.as_str()),
+            "--answers" | "-a" => options.answers = Some(value()?.as_str()),
+            "--iterations" | "-n" => options.iterations = value()?.parse::<usize>().map_err(|_| anyhow!(USAGE))?,
+            "--format" | "-f" => options.format = value()?.parse::<Format>()?,
+            _ if day.is_none() => day = Some(arg.as_str()),
+            _ => bail!(USAGE),
+        }
+    }
+
+    options.days = match day.ok_or_else(|| anyhow!(USAGE))? {
+        "all" => SOLUTIONS.iter().collect(),
+        day => vec![find_day(day)?],
+    };
+    Ok(options)
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let options = options(args)?;
+    if options.days.len() > 1 && options.input.is_some() {
+        bail!("An input file can only be given for a single day");
+    }
+    for solution in options.days {
+        let input = match options.input {
+            Some(path) => read_input(path)?,
+            None => solution.input()?,
+        };
+        match options.format {
+            Format::Text => solution.run(&input, options.part)?,
+            Format::Json => {
+                let answers = solution.solve(&input, options.part)?.iter()
+                    .map(|(part, answer)| format!("\"part{}\": {}", part, answer.to_json()))
+                    .collect::<Vec<_>>();
+                println!("{{\"day\": \"{}\", {}}}", solution.name, answers.join(", "));
+            },
+            Format::Csv => bail!("CSV output is only available for bench"),
+        }
+    }
+    Ok(())
+}
+
+fn bench(args: &[String]) -> Result<()> {
+    let options = options(args)?;
+    if options.days.len() > 1 && options.input.is_some() {
+        bail!("An input file can only be given for a single day");
+    }
+    let mut timings = Vec::new();
+    for solution in options.days {
+        let (name, n) = (solution.name, options.iterations);
+        let input = match options.input {
+            Some("-") => read_input("-")?,
+            Some(path) => {
+                timings.push(bench::measure(name, "input", n, || read_input(path))?);
+                read_input(path)?
+            },
+            None => {
+                timings.push(bench::measure(name, "input", n, || solution.input())?);
+                solution.input()?
+            },
+        };
+        timings.push(bench::measure(name, "parse", n, || (solution.parse)(&input))?);
+        let parsed = (solution.parse)(&input)?;
+        for (part, phase) in [(1, "part1"), (2, "part2")].iter().copied() {
+            if options.part.is_some_and(|p| p != part) {
+                continue;
+            }
+            let f = solution.part(part)?;
+            timings.push(bench::measure(name, phase, n, || f(parsed.as_ref()))?);
+        }
+    }
+    print!("{}", bench::report(&timings, options.format));
+    Ok(())
+}
+
+fn verify(args: &[String]) -> Result<()> {
+    let args = if args.first().is_none_or(|a| a.starts_with('-')) {
+        [&["all".to_string()], args].concat()
+    } else {
+        args.to_vec()
+    };
+    let options = options(&args)?;
+    let answers = Answers::load(options.answers.unwrap_or(Answers::PATH))?;
+    let checks = options.days.into_iter().flat_map(|s| answers::verify(s, &answers)).collect::<Vec<_>>();
+    checks.iter().for_each(|check| println!("{}", check));
+
+    let count = |f: fn(&Status) -> bool| checks.iter().filter(|c| f(&c.status)).count();
+    let failures = checks.iter().filter(|c| c.is_failure()).count();
+    println!("{} correct, {} failed, {} without answer, {} without input",
+        count(|s| *s == Status::Correct), failures,
+        count(|s| matches!(s, Status::MissingAnswer(_))), count(|s| *s == Status::MissingInput));
+    if failures > 0 {
+        bail!("{} of {} checks failed", failures, checks.len());
+    }
+    Ok(())
+}
+
+/// Runs the command given on the command line.
+/// 
+/// # Arguments
+/// 
+/// * `main`: Default solution to run without a command
+/// 
+/// # Errors
+/// 
+/// Invalid command line, and any error returned by the solutions.
+pub fn main(main: fn() -> Result<()>) -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        None => main(),
+        Some("list") => {
+            SOLUTIONS.iter().for_each(|s| println!("{:>2} {} ({})", s.day, s.name, s.data));
+            Ok(())
+        },
+        Some("run") => run(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some(_) => bail!(USAGE),
+    }
+}
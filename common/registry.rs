@@ -0,0 +1,158 @@
+//! Registry of the solutions declared with the `solution!` macro.
+
+use std::any::Any;
+use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::answer::Answer;
+
+/// Puzzle input parsed by a solution, shared by both of its parts.
+pub type Parsed = Box<dyn Any>;
+
+/// Function parsing the puzzle input of a solution.
+pub type Parse = fn(&str) -> Result<Parsed>;
+
+/// Function solving one part of a puzzle from the parsed input.
+pub type Part = fn(&dyn Any) -> Result<Answer>;
+
+/// A solution registered by the `solution!` macro.
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    /// Day of the puzzle.
+    pub day: u32,
+    /// Name of the solution module, e.g. `"day3"`.
+    pub name: &'static str,
+    /// Parser of the puzzle input.
+    pub parse: Parse,
+    /// Solution of the first part.
+    pub part1: Part,
+    /// Solution of the second part.
+    pub part2: Part,
+    /// Path of the puzzle input, relative to the crate root.
+    pub data: &'static str,
+}
+
+impl Solution {
+    /// Looks up a registered solution.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `day`: Day number (`"3"`) or module name (`"day3"`)
+    pub fn find(day: &str) -> Option<&'static Solution> {
+        let number = day.parse::<u32>().ok();
+        crate::SOLUTIONS.iter().find(|s| s.name == day || Some(s.day) == number)
+    }
+
+    /// Reads the puzzle input from [`data`](Self::data).
+    /// 
+    /// # Errors
+    /// 
+    /// I/O errors while reading the input file.
+    pub fn input(&self) -> Result<String> {
+        fs::read_to_string(self.data).map_err(|e| anyhow!("{}: {}", self.data, e))
+    }
+
+    /// Returns the solution of a part.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `part`: Part number, `1` or `2`
+    /// 
+    /// # Errors
+    /// 
+    /// Part numbers other than `1` and `2`.
+    pub fn part(&self, part: u8) -> Result<Part> {
+        match part {
+            1 => Ok(self.part1),
+            2 => Ok(self.part2),
+            _ => bail!("No part {} in {}", part, self.name),
+        }
+    }
+
+    /// Runs the solution on the given input.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `input`: Puzzle input string
+    /// * `part`: Part to run, or `None` to run both
+    /// 
+    /// # Errors
+    /// 
+    /// Invalid part number, and any error returned by the solution.
+    pub fn solve(&self, input: &str, part: Option<u8>) -> Result<Vec<(u8, Answer)>> {
+        let parts = match part {
+            Some(part) => vec![(part, self.part(part)?)],
+            None => vec![(1, self.part1), (2, self.part2)],
+        };
+        let parsed = (self.parse)(input)?;
+        parts.into_iter().map(|(part, f)| Ok((part, f(parsed.as_ref())?))).collect()
+    }
+
+    /// Runs the solution on the given input and prints the results to standard output.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `input`: Puzzle input string
+    /// * `part`: Part to run, or `None` to run both
+    /// 
+    /// # Errors
+    /// 
+    /// Invalid part number, and any error returned by the solution.
+    pub fn run(&self, input: &str, part: Option<u8>) -> Result<()> {
+        let answers = self.solve(input, part)?.into_iter().map(|(_, a)| a).collect::<Vec<_>>();
+        println!("{}", Answer::line(self.name, &answers));
+        Ok(())
+    }
+}
+
+/// Recovers the parsed input of a solution, with the type inferred from its parser.
+/// 
+/// # Arguments
+/// 
+/// * `parse`: Parser of the solution, only used for type inference
+/// * `parsed`: Parsed input passed to the part
+/// 
+/// # Errors
+/// 
+/// Input parsed by a different solution.
+#[doc(hidden)]
+pub fn downcast<T: Any>(_parse: fn(&str) -> Result<T>, parsed: &dyn Any) -> Result<&T> {
+    parsed.downcast_ref::<T>().ok_or_else(|| anyhow!("Input was parsed by another solution"))
+}
+
+/// Extracts the day number from a solution module name at compile time.
+/// 
+/// # Arguments
+/// 
+/// * `name`: Module name, e.g. `"day3"`
+#[doc(hidden)]
+pub const fn day_number(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut day = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() {
+            day = day * 10 + (bytes[i] - b'0') as u32;
+        }
+        i += 1;
+    }
+    day
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registered() {
+        assert_eq!(day_number("day17"), 17);
+        assert_eq!(crate::SOLUTIONS.iter().map(|s| s.day).collect::<Vec<_>>(), (1..=crate::SOLUTIONS.len() as u32).collect::<Vec<_>>());
+        let day1 = Solution::find("1").unwrap();
+        assert_eq!((day1.name, day1.data), ("day1", "data/day1.txt"));
+        assert_eq!(Solution::find("day1").unwrap().day, 1);
+        assert!(Solution::find("day99").is_none());
+        assert!(day1.part(3).is_err());
+        let parsed = (day1.parse)("1000\n1020").unwrap();
+        assert!((day1.part1)(parsed.as_ref()).is_ok());
+        assert!((day1.part1)(&"1\n2").is_err());
+    }
+}
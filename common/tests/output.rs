@@ -0,0 +1,119 @@
+//! Checks of the machine readable output of the runner binaries, shared by their command line tests.
+
+use std::process::Command;
+
+/// Runs a runner binary from the crate root and returns its standard output.
+/// 
+/// # Arguments
+/// 
+/// * `exe`: Path of the binary, from `env!("CARGO_BIN_EXE_<name>")`
+/// * `args`: Command line arguments
+/// 
+/// # Panics
+/// 
+/// If the binary can't be started or exits with an error.
+pub fn run(exe: &str, args: &[&str]) -> String {
+    let output = Command::new(exe).args(args).current_dir(env!("CARGO_MANIFEST_DIR")).output().unwrap();
+    assert!(output.status.success(), "{} {}: {}", exe, args.join(" "), String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn string(s: &str) -> Option<&str> {
+    let s = s.strip_prefix('"')?;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some(&s[i + 1..]),
+            '\\' => match chars.next()?.1 {
+                '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => {},
+                'u' if (0..4).all(|_| chars.next().is_some_and(|(_, c)| c.is_ascii_hexdigit())) => {},
+                _ => return None,
+            },
+            c if c.is_control() => return None,
+            _ => {},
+        }
+    }
+    None
+}
+
+fn number(s: &str) -> Option<&str> {
+    let end = s.find(|c: char| !c.is_ascii_digit() && !"+-.eE".contains(c)).unwrap_or(s.len());
+    s[..end].parse::<f64>().ok()?;
+    Some(&s[end..])
+}
+
+fn member(s: &str) -> Option<&str> {
+    let s = string(s.trim_start())?;
+    value(s.trim_start().strip_prefix(':')?)
+}
+
+fn sequence(s: &str, end: char, item: fn(&str) -> Option<&str>) -> Option<&str> {
+    let mut s = s.trim_start();
+    if let Some(rest) = s.strip_prefix(end) {
+        return Some(rest);
+    }
+    loop {
+        s = item(s)?.trim_start();
+        match s.strip_prefix(end) {
+            Some(rest) => return Some(rest),
+            None => s = s.strip_prefix(',')?,
+        }
+    }
+}
+
+fn value(s: &str) -> Option<&str> {
+    let s = s.trim_start();
+    match s.chars().next()? {
+        '{' => sequence(&s[1..], '}', member),
+        '[' => sequence(&s[1..], ']', value),
+        '"' => string(s),
+        't' => s.strip_prefix("true"),
+        'f' => s.strip_prefix("false"),
+        'n' => s.strip_prefix("null"),
+        _ => number(s),
+    }
+}
+
+fn is_json(output: &str) -> bool {
+    let mut rest = output;
+    while !rest.trim().is_empty() {
+        match value(rest) {
+            Some(next) => rest = next,
+            None => return false,
+        }
+    }
+    !output.trim().is_empty()
+}
+
+/// Asserts that the output consists of JSON values only, e.g. one object per line.
+/// 
+/// # Arguments
+/// 
+/// * `output`: Standard output of the runner
+pub fn assert_json(output: &str) {
+    assert!(is_json(output), "Invalid JSON output:\n{}", output);
+}
+
+/// Asserts that the output is a CSV table with a header and rows of the same width.
+/// 
+/// # Arguments
+/// 
+/// * `output`: Standard output of the runner
+pub fn assert_csv(output: &str) {
+    let mut lines = output.lines();
+    let columns = lines.next().map_or(0, |header| header.split(',').count());
+    assert!(columns > 1, "Missing CSV header in\n{}", output);
+    for line in lines {
+        assert_eq!(line.split(',').count(), columns, "Invalid CSV row {:?} in\n{}", line, output);
+    }
+}
+
+#[test]
+fn json() {
+    for valid in &["{}", "[1, -2.5e3, \"a\\\"b\\u00e9\", true, null]\n", "{\"a\": {\"b\": [false]}}\n{\"c\": \"\"}\n"] {
+        assert!(is_json(valid), "{:?}", valid);
+    }
+    for invalid in &["", "{", "[1,]", "{\"a\" 1}", "\"a", "|0|\n{}", "{} x"] {
+        assert!(!is_json(invalid), "{:?}", invalid);
+    }
+}